@@ -0,0 +1,332 @@
+//! Batch FROST signing of many messages in one session.
+//!
+//! Signing each input of a large transaction separately costs one key-share
+//! deserialization and two round trips per input. Here every round carries
+//! the nonce commitments (round 1) or partial signatures (round 2) for all N
+//! messages at once, so N signatures take two round trips in total.
+
+use async_channel::{Receiver, Sender};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use super::ciphersuite::{load_key_share, FrostCiphersuite};
+use super::rounds::{
+    aggregate_with_blame, commit, sign_share, signing_tweak, PartialSignature, PublicCommitments,
+    RoundTransport,
+};
+use super::signing::{
+    verifying_key, FrostKeyShare, ProtocolMessage, SchnorrSignature, SigningOptions,
+};
+use super::transcript::SigningTranscript;
+use crate::bench::{BenchmarkRecorder, BenchmarkReport};
+
+/// Round 1 payload: one commitment per message, in message order.
+#[derive(Debug, Serialize, Deserialize)]
+struct BatchCommitments {
+    commitments: Vec<PublicCommitments>,
+}
+
+/// Round 2 payload: one partial signature per message, in message order.
+#[derive(Debug, Serialize, Deserialize)]
+struct BatchPartialSignatures {
+    partial_signatures: Vec<PartialSignature>,
+}
+
+/// Result of FROST batch signing.
+#[derive(Debug)]
+pub struct FrostBatchSigningResult {
+    pub success: bool,
+    /// One signature per message hash, in input order
+    pub signatures: Vec<SchnorrSignature>,
+    /// The x-only key every signature verifies against
+    pub output_key: Option<[u8; 32]>,
+    /// Error message if failed
+    pub error: Option<String>,
+    /// Duration of the protocol
+    pub duration_secs: f64,
+    /// Detailed benchmark report (if benchmarking enabled)
+    pub benchmark: Option<BenchmarkReport>,
+    /// Signers (index at keygen) whose partial signatures failed verification
+    pub blamed_parties: Vec<u16>,
+    /// Public record of each signature for offline verification, in input order
+    pub transcripts: Vec<SigningTranscript>,
+}
+
+/// Run FROST threshold signing over many message hashes in one session.
+///
+/// All messages are signed under the same `options` (mode and Taproot
+/// merkle root). The protocol takes exactly two rounds regardless of the
+/// number of messages.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_frost_batch_signing(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    message_hashes: &[[u8; 32]],
+    key_share_data: &[u8],
+    options: &SigningOptions,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostBatchSigningResult {
+    let start = std::time::Instant::now();
+    let mut recorder = BenchmarkRecorder::new("FROST-Batch-Signing", party_index, session_id);
+
+    info!("========================================");
+    info!("  FROST BATCH SIGNING STARTING");
+    info!("========================================");
+    info!("Party index: {}", party_index);
+    info!("Parties at keygen: {:?}", parties_at_keygen);
+    info!("Session ID: {}", session_id);
+    info!("Messages: {}", message_hashes.len());
+
+    let mut blamed_parties = Vec::new();
+    let result = batch_sign(
+        party_index,
+        parties_at_keygen,
+        session_id,
+        message_hashes,
+        key_share_data,
+        options,
+        incoming_rx,
+        outgoing_tx,
+        &mut recorder,
+        &mut blamed_parties,
+    )
+    .await;
+
+    let benchmark = if options.enable_benchmark {
+        recorder.complete();
+        let report = recorder.report();
+        report.log();
+        Some(report)
+    } else {
+        None
+    };
+
+    match result {
+        Ok(transcripts) => {
+            info!(
+                "FROST batch signing of {} message(s) completed in {:.2}s",
+                transcripts.len(),
+                start.elapsed().as_secs_f64()
+            );
+            FrostBatchSigningResult {
+                success: true,
+                signatures: transcripts.iter().map(|t| t.signature).collect(),
+                output_key: transcripts.first().map(|t| t.output_key),
+                error: None,
+                duration_secs: start.elapsed().as_secs_f64(),
+                benchmark,
+                blamed_parties,
+                transcripts,
+            }
+        }
+        Err(e) => {
+            error!("FROST batch signing failed: {}", e);
+            FrostBatchSigningResult {
+                success: false,
+                signatures: Vec::new(),
+                output_key: None,
+                error: Some(e),
+                duration_secs: start.elapsed().as_secs_f64(),
+                benchmark,
+                blamed_parties,
+                transcripts: Vec::new(),
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn batch_sign(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    message_hashes: &[[u8; 32]],
+    key_share_data: &[u8],
+    options: &SigningOptions,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+    recorder: &mut BenchmarkRecorder,
+    blamed_parties: &mut Vec<u16>,
+) -> Result<Vec<SigningTranscript>, String> {
+    if usize::from(party_index) >= parties_at_keygen.len() {
+        return Err(format!(
+            "Party index {} out of range for {} signers",
+            party_index,
+            parties_at_keygen.len()
+        ));
+    }
+    if message_hashes.is_empty() {
+        return Err("No messages to sign".to_string());
+    }
+
+    let step_start = std::time::Instant::now();
+    let key_share: FrostKeyShare = load_key_share(key_share_data, FrostCiphersuite::Bitcoin)?;
+    if parties_at_keygen.get(usize::from(party_index)) != Some(&key_share.i) {
+        return Err(format!(
+            "Party index {} does not match key share index {} in signers {:?}",
+            party_index, key_share.i, parties_at_keygen
+        ));
+    }
+    let output_key = verifying_key(key_share.as_ref(), options)?;
+    let tweak = signing_tweak(key_share.as_ref(), options.mode, options.taproot_tweak)?;
+    recorder.record_step("1. Deserialize key_share", step_start.elapsed());
+
+    let others: Vec<u16> = (0..parties_at_keygen.len() as u16)
+        .filter(|&j| j != party_index)
+        .collect();
+    let mut transport = RoundTransport::new(session_id, party_index, incoming_rx, outgoing_tx);
+
+    // Round 1: commit to one nonce pair per message
+    let step_start = std::time::Instant::now();
+    let (nonces, commitments): (Vec<_>, Vec<_>) =
+        message_hashes.iter().map(|_| commit(&key_share)).unzip();
+    transport
+        .broadcast(
+            1,
+            &BatchCommitments {
+                commitments: commitments.clone(),
+            },
+        )
+        .await?;
+    let mut round1 = transport.collect::<BatchCommitments>(1, &others).await?;
+    round1.insert(party_index, BatchCommitments { commitments });
+    for (j, batch) in &round1 {
+        if batch.commitments.len() != message_hashes.len() {
+            return Err(format!(
+                "Party {} sent {} commitments for {} messages",
+                j,
+                batch.commitments.len(),
+                message_hashes.len()
+            ));
+        }
+    }
+    recorder.record_step("2. Round 1 (batch commitments)", step_start.elapsed());
+
+    // Commitments per message, keyed by index at keygen
+    let commitments_for = |m: usize| -> Vec<(u16, PublicCommitments)> {
+        round1
+            .iter()
+            .map(|(&j, batch)| (parties_at_keygen[j as usize], batch.commitments[m].clone()))
+            .collect()
+    };
+
+    // Round 2: one partial signature per message
+    let step_start = std::time::Instant::now();
+    let mut partial_signatures = Vec::with_capacity(message_hashes.len());
+    for (m, (nonce, msg)) in nonces.into_iter().zip(message_hashes).enumerate() {
+        partial_signatures.push(
+            sign_share(
+                &key_share,
+                nonce,
+                msg,
+                &commitments_for(m),
+                tweak,
+                options.nonce_ledger.as_ref(),
+            )
+            .await?,
+        );
+    }
+    transport
+        .broadcast(
+            2,
+            &BatchPartialSignatures {
+                partial_signatures: partial_signatures.clone(),
+            },
+        )
+        .await?;
+    let mut round2 = transport
+        .collect::<BatchPartialSignatures>(2, &others)
+        .await?;
+    round2.insert(party_index, BatchPartialSignatures { partial_signatures });
+    recorder.record_step(
+        "3. Round 2 (batch partial signatures)",
+        step_start.elapsed(),
+    );
+
+    // Aggregate and self-verify each message
+    let step_start = std::time::Instant::now();
+    let mut transcripts = Vec::with_capacity(message_hashes.len());
+    for (m, msg) in message_hashes.iter().enumerate() {
+        let shares = round1
+            .iter()
+            .map(|(&j, batch)| {
+                let partial = round2
+                    .get(&j)
+                    .and_then(|p| p.partial_signatures.get(m))
+                    .ok_or_else(|| {
+                        format!("Party {} sent no partial signature for message {}", j, m)
+                    })?;
+                Ok((
+                    parties_at_keygen[j as usize],
+                    batch.commitments[m].clone(),
+                    partial.clone(),
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let signature = aggregate_with_blame(key_share.as_ref(), &shares, msg, tweak, &output_key)
+            .map_err(|(e, blamed)| {
+                *blamed_parties = blamed;
+                format!("Message {}: {}", m, e)
+            })?;
+        transcripts.push(SigningTranscript::new(
+            session_id,
+            parties_at_keygen,
+            msg,
+            key_share.as_ref(),
+            options,
+            signature,
+            output_key,
+        ));
+    }
+    recorder.record_step("4. Aggregate and verify signatures", step_start.elapsed());
+
+    Ok(transcripts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::local;
+    use super::super::taproot::{tweak_public_key, TaprootTweak};
+    use super::*;
+
+    #[test]
+    fn test_batch_signing_2_of_3() {
+        let key_shares = local::key_shares(2, 3);
+        let signers = [0u16, 2];
+        let message_hashes: Vec<[u8; 32]> = (1..=3u8).map(|m| [m; 32]).collect();
+        let options = SigningOptions::default();
+
+        let results = local::run_parties(
+            signers.len() as u16,
+            |m: &ProtocolMessage| (m.sender, m.recipient),
+            |i, rx, tx| {
+                run_frost_batch_signing(
+                    i,
+                    &signers,
+                    "batch",
+                    &message_hashes,
+                    &key_shares[usize::from(signers[usize::from(i)])],
+                    &options,
+                    rx,
+                    tx,
+                )
+            },
+        );
+
+        let key_share: FrostKeyShare =
+            load_key_share(&key_shares[0], FrostCiphersuite::Bitcoin).unwrap();
+        let internal_key = key_share.shared_public_key().to_bytes(true)[1..].to_vec();
+        let (output_key, _parity) = tweak_public_key(&internal_key, TaprootTweak::KeyOnly).unwrap();
+        for result in results {
+            assert!(result.success, "batch signing failed: {:?}", result.error);
+            assert_eq!(result.output_key, Some(output_key));
+            assert_eq!(result.signatures.len(), message_hashes.len());
+            assert_eq!(result.transcripts.len(), message_hashes.len());
+            for (signature, msg) in result.signatures.iter().zip(&message_hashes) {
+                assert!(signature.verify(&output_key, msg).is_ok());
+            }
+        }
+    }
+}
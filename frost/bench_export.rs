@@ -0,0 +1,381 @@
+//! Machine-readable export, aggregation and comparison of benchmark reports.
+//!
+//! A [`BenchmarkReport`] is converted to a [`RunReport`] that serializes to
+//! JSON or CSV. Many runs aggregate into an [`AggregateReport`] with
+//! min/median/p95/max per step, and two report files (JSON reports, or the
+//! log of a Verilog testbench run) can be diffed step by step.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bench::BenchmarkReport;
+
+/// Step holding the total duration of a run.
+pub const TOTAL_STEP: &str = "Total";
+
+/// Clock period of the Verilog testbenches (100 MHz).
+pub const TESTBENCH_CLOCK_PERIOD_NS: f64 = 10.0;
+
+/// Duration of one benchmark step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepTiming {
+    pub step: String,
+    pub duration_secs: f64,
+}
+
+/// One benchmark run in exportable form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+    /// Benchmark name, e.g. "FROST-Keygen"
+    pub name: String,
+    pub party_index: u16,
+    pub session_id: String,
+    /// Steps in recording order, followed by the total
+    pub steps: Vec<StepTiming>,
+}
+
+impl RunReport {
+    pub fn from_report(report: &BenchmarkReport) -> Self {
+        let mut steps: Vec<StepTiming> = report
+            .steps
+            .iter()
+            .map(|s| StepTiming {
+                step: s.name.clone(),
+                duration_secs: s.duration.as_secs_f64(),
+            })
+            .collect();
+        steps.push(StepTiming {
+            step: TOTAL_STEP.to_string(),
+            duration_secs: report.total_duration.as_secs_f64(),
+        });
+        Self {
+            name: report.name.clone(),
+            party_index: report.party_index,
+            session_id: report.session_id.clone(),
+            steps,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize report: {}", e))
+    }
+
+    /// CSV with one row per step.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("name,party_index,session_id,step,duration_secs\n");
+        for step in &self.steps {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{:.9}",
+                csv_field(&self.name),
+                self.party_index,
+                csv_field(&self.session_id),
+                csv_field(&step.step),
+                step.duration_secs
+            );
+        }
+        csv
+    }
+}
+
+/// Statistics of one step over many runs, in seconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepStats {
+    pub step: String,
+    /// Number of runs that recorded the step
+    pub runs: usize,
+    pub min_secs: f64,
+    pub median_secs: f64,
+    pub p95_secs: f64,
+    pub max_secs: f64,
+}
+
+impl StepStats {
+    fn from_samples(step: &str, samples: &mut [f64]) -> Self {
+        samples.sort_by(f64::total_cmp);
+        let n = samples.len();
+        let median = if n % 2 == 0 {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2.0
+        } else {
+            samples[n / 2]
+        };
+        // Nearest-rank percentile
+        let p95 = samples[(n * 95).div_ceil(100).max(1) - 1];
+        Self {
+            step: step.to_string(),
+            runs: n,
+            min_secs: samples[0],
+            median_secs: median,
+            p95_secs: p95,
+            max_secs: samples[n - 1],
+        }
+    }
+}
+
+/// Per-step statistics over many runs of one benchmark.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateReport {
+    pub name: String,
+    /// Number of runs aggregated
+    pub runs: usize,
+    /// Steps in first-recorded order
+    pub steps: Vec<StepStats>,
+}
+
+impl AggregateReport {
+    /// Aggregate runs step by step. Steps missing from some runs are
+    /// aggregated over the runs that have them.
+    pub fn from_runs(name: &str, runs: &[RunReport]) -> Self {
+        let mut order: Vec<&str> = Vec::new();
+        let mut samples: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+        for run in runs {
+            for step in &run.steps {
+                samples
+                    .entry(step.step.as_str())
+                    .or_insert_with(|| {
+                        order.push(step.step.as_str());
+                        Vec::new()
+                    })
+                    .push(step.duration_secs);
+            }
+        }
+        let steps = order
+            .into_iter()
+            .filter_map(|step| {
+                let samples = samples.get_mut(step)?;
+                Some(StepStats::from_samples(step, samples))
+            })
+            .collect();
+        Self {
+            name: name.to_string(),
+            runs: runs.len(),
+            steps,
+        }
+    }
+
+    /// Total cycles of a Verilog testbench run, as a single-step report.
+    ///
+    /// Reads the `Total clock cycles: N` line the testbenches print and
+    /// converts cycles to seconds at `clock_period_ns`.
+    pub fn from_testbench_log(log: &str, clock_period_ns: f64) -> Result<Self, String> {
+        let cycles: u64 = log
+            .lines()
+            .find_map(|line| {
+                let line = line.trim();
+                line.strip_prefix("Total clock cycles:")
+                    .or_else(|| line.strip_prefix("Total cycles:"))
+                    .or_else(|| line.strip_prefix("Hardware cycles (actual):"))
+                    .and_then(|n| n.trim().parse().ok())
+            })
+            .ok_or_else(|| "No cycle count found in testbench log".to_string())?;
+        let secs = cycles as f64 * clock_period_ns * 1e-9;
+        Ok(Self {
+            name: "Verilog testbench".to_string(),
+            runs: 1,
+            steps: vec![StepStats::from_samples(TOTAL_STEP, &mut [secs])],
+        })
+    }
+
+    pub fn step(&self, step: &str) -> Option<&StepStats> {
+        self.steps.iter().find(|s| s.step == step)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize report: {}", e))
+    }
+
+    /// CSV with one row per step.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("name,step,runs,min_secs,median_secs,p95_secs,max_secs\n");
+        for s in &self.steps {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{:.9},{:.9},{:.9},{:.9}",
+                csv_field(&self.name),
+                csv_field(&s.step),
+                s.runs,
+                s.min_secs,
+                s.median_secs,
+                s.p95_secs,
+                s.max_secs
+            );
+        }
+        csv
+    }
+}
+
+/// Quote a CSV field if needed.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Median duration of one step in two reports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepDiff {
+    pub step: String,
+    pub baseline_secs: Option<f64>,
+    pub current_secs: Option<f64>,
+}
+
+impl StepDiff {
+    /// `current / baseline`, above 1.0 for a slowdown.
+    pub fn ratio(&self) -> Option<f64> {
+        match (self.baseline_secs, self.current_secs) {
+            (Some(b), Some(c)) if b > 0.0 => Some(c / b),
+            _ => None,
+        }
+    }
+}
+
+/// Step-by-step comparison of two reports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportDiff {
+    pub baseline: String,
+    pub current: String,
+    pub steps: Vec<StepDiff>,
+}
+
+/// Compare the median of every step of `current` against `baseline`.
+pub fn compare_reports(baseline: &AggregateReport, current: &AggregateReport) -> ReportDiff {
+    let mut steps: Vec<StepDiff> = baseline
+        .steps
+        .iter()
+        .map(|b| StepDiff {
+            step: b.step.clone(),
+            baseline_secs: Some(b.median_secs),
+            current_secs: current.step(&b.step).map(|c| c.median_secs),
+        })
+        .collect();
+    steps.extend(
+        current
+            .steps
+            .iter()
+            .filter(|c| baseline.step(&c.step).is_none())
+            .map(|c| StepDiff {
+                step: c.step.clone(),
+                baseline_secs: None,
+                current_secs: Some(c.median_secs),
+            }),
+    );
+    ReportDiff {
+        baseline: baseline.name.clone(),
+        current: current.name.clone(),
+        steps,
+    }
+}
+
+impl std::fmt::Display for ReportDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let secs = |s: Option<f64>| s.map_or("-".to_string(), |s| format!("{:.6}s", s));
+        writeln!(f, "Baseline: {}", self.baseline)?;
+        writeln!(f, "Current:  {}", self.current)?;
+        for diff in &self.steps {
+            write!(
+                f,
+                "{:<48} {:>14} {:>14}",
+                diff.step,
+                secs(diff.baseline_secs),
+                secs(diff.current_secs)
+            )?;
+            match diff.ratio() {
+                Some(ratio) => writeln!(f, " {:>9.2}x", ratio)?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Load a report file for comparison.
+///
+/// Accepts an exported run, a JSON array of runs, an aggregate report, or
+/// the log of a Verilog testbench run.
+pub fn load_report_file(path: &str, clock_period_ns: f64) -> Result<AggregateReport, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    if let Ok(report) = serde_json::from_str::<AggregateReport>(&data) {
+        return Ok(report);
+    }
+    if let Ok(run) = serde_json::from_str::<RunReport>(&data) {
+        let name = run.name.clone();
+        return Ok(AggregateReport::from_runs(&name, &[run]));
+    }
+    if let Ok(runs) = serde_json::from_str::<Vec<RunReport>>(&data) {
+        let name = runs.first().map(|r| r.name.clone()).unwrap_or_default();
+        return Ok(AggregateReport::from_runs(&name, &runs));
+    }
+    AggregateReport::from_testbench_log(&data, clock_period_ns)
+        .map_err(|e| format!("{} is not a benchmark report: {}", path, e))
+}
+
+/// Diff two report files.
+pub fn compare_report_files(
+    baseline_path: &str,
+    current_path: &str,
+    clock_period_ns: f64,
+) -> Result<ReportDiff, String> {
+    let baseline = load_report_file(baseline_path, clock_period_ns)?;
+    let current = load_report_file(current_path, clock_period_ns)?;
+    Ok(compare_reports(&baseline, &current))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(steps: &[(&str, f64)]) -> RunReport {
+        RunReport {
+            name: "FROST-Keygen".to_string(),
+            party_index: 0,
+            session_id: "s".to_string(),
+            steps: steps
+                .iter()
+                .map(|(step, duration_secs)| StepTiming {
+                    step: step.to_string(),
+                    duration_secs: *duration_secs,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_runs_per_step() {
+        let runs: Vec<RunReport> = (1..=20)
+            .map(|i| run(&[("1. Setup", 0.5), (TOTAL_STEP, f64::from(i))]))
+            .collect();
+        let report = AggregateReport::from_runs("FROST-Keygen", &runs);
+        assert_eq!(report.runs, 20);
+        assert_eq!(report.steps[0].step, "1. Setup");
+
+        let total = report.step(TOTAL_STEP).unwrap();
+        assert_eq!(total.min_secs, 1.0);
+        assert_eq!(total.median_secs, 10.5);
+        assert_eq!(total.p95_secs, 19.0);
+        assert_eq!(total.max_secs, 20.0);
+
+        let csv = run(&[("3.1 Round 1: a, b", 0.25)]).to_csv();
+        assert!(csv.ends_with("FROST-Keygen,0,s,\"3.1 Round 1: a, b\",0.250000000\n"));
+    }
+
+    #[test]
+    fn test_compare_against_testbench_cycles() {
+        let log = "[0] Starting FROST DKG protocol...\nTotal clock cycles: 1500\n";
+        let hardware = AggregateReport::from_testbench_log(log, TESTBENCH_CLOCK_PERIOD_NS).unwrap();
+        let software = AggregateReport::from_runs(
+            "FROST-Keygen",
+            &[run(&[("1. Setup", 0.001), (TOTAL_STEP, 0.0015)])],
+        );
+
+        let diff = compare_reports(&hardware, &software);
+        assert_eq!(diff.steps[0].step, TOTAL_STEP);
+        assert!((diff.steps[0].ratio().unwrap() - 100.0).abs() < 1e-9);
+        assert_eq!(diff.steps[1].baseline_secs, None);
+        assert!(AggregateReport::from_testbench_log("no cycles", 10.0).is_err());
+    }
+}
@@ -0,0 +1,714 @@
+//! In-process benchmark harness behind the `frost-bench` binary.
+//!
+//! Runs all parties of keygen and signing in one process, each on its own
+//! thread, over an in-memory relay. Configurations span a grid of (t, n),
+//! message counts, ciphersuites and wire codecs. Every configuration is
+//! repeated and aggregated per step, see [`super::bench_export`]. Timings
+//! are taken from party 0.
+
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use super::bench_export::{
+    compare_report_files, AggregateReport, RunReport, StepTiming, TESTBENCH_CLOCK_PERIOD_NS,
+    TOTAL_STEP,
+};
+use super::ciphersuite::{run_frost_signing_with_ciphersuite_benchmark, FrostCiphersuite};
+use super::keygen::{self, run_frost_keygen_with_benchmark, KeygenOptions};
+use super::local::run_parties_over;
+use super::op_count::{profile_keygen_ops, profile_signing_ops, OpProfile};
+use super::signing;
+
+/// Threshold and number of parties of one grid point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchGridPoint {
+    pub threshold: u16,
+    pub parties: u16,
+}
+
+impl std::str::FromStr for BenchGridPoint {
+    type Err = String;
+
+    /// Parses `t-of-n`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (t, n) = s
+            .split_once("-of-")
+            .ok_or_else(|| format!("Expected t-of-n, got {}", s))?;
+        let threshold: u16 = t.parse().map_err(|_| format!("Invalid threshold: {}", t))?;
+        let parties: u16 = n
+            .parse()
+            .map_err(|_| format!("Invalid number of parties: {}", n))?;
+        if threshold < 2 || threshold > parties {
+            return Err(format!("Invalid threshold {}-of-{}", threshold, parties));
+        }
+        Ok(Self { threshold, parties })
+    }
+}
+
+/// Wire encoding of protocol messages between parties.
+///
+/// The relay encodes every message it delivers and decodes it again, as a
+/// network hop would, so the codec's cost and the message size show up in
+/// the parties' network wait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BenchCodec {
+    /// JSON envelope, as the protocol messages serialize with serde
+    #[default]
+    Json,
+    /// Fixed binary framing of the envelope fields around the payload
+    Binary,
+}
+
+impl BenchCodec {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BenchCodec::Json => "json",
+            BenchCodec::Binary => "binary",
+        }
+    }
+
+    fn encode<M: Envelope>(self, msg: &M) -> Result<Vec<u8>, String> {
+        match self {
+            BenchCodec::Json => serde_json::to_vec(msg).map_err(|e| e.to_string()),
+            BenchCodec::Binary => {
+                let (session_id, sender, recipient, round, payload, seq) = msg.fields();
+                let session_len = u16::try_from(session_id.len())
+                    .map_err(|_| "Session ID too long".to_string())?;
+                let payload_len =
+                    u32::try_from(payload.len()).map_err(|_| "Payload too long".to_string())?;
+                let mut bytes = Vec::with_capacity(21 + session_id.len() + payload.len());
+                bytes.extend_from_slice(&sender.to_be_bytes());
+                bytes.push(u8::from(recipient.is_some()));
+                bytes.extend_from_slice(&recipient.unwrap_or(0).to_be_bytes());
+                bytes.extend_from_slice(&round.to_be_bytes());
+                bytes.extend_from_slice(&seq.to_be_bytes());
+                bytes.extend_from_slice(&session_len.to_be_bytes());
+                bytes.extend_from_slice(session_id.as_bytes());
+                bytes.extend_from_slice(&payload_len.to_be_bytes());
+                bytes.extend_from_slice(payload);
+                Ok(bytes)
+            }
+        }
+    }
+
+    fn decode<M: Envelope>(self, bytes: &[u8]) -> Result<M, String> {
+        match self {
+            BenchCodec::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+            BenchCodec::Binary => {
+                let mut rest = bytes;
+                let mut take = |len: usize| {
+                    if rest.len() < len {
+                        return Err("Truncated message".to_string());
+                    }
+                    let (field, tail) = rest.split_at(len);
+                    rest = tail;
+                    Ok(field)
+                };
+                let u16_at = |b: &[u8]| u16::from_be_bytes([b[0], b[1]]);
+                let sender = u16_at(take(2)?);
+                let has_recipient = take(1)?[0] != 0;
+                let recipient = u16_at(take(2)?);
+                let round = u16_at(take(2)?);
+                let mut seq = [0u8; 8];
+                seq.copy_from_slice(take(8)?);
+                let session_len = usize::from(u16_at(take(2)?));
+                let session_id = String::from_utf8(take(session_len)?.to_vec())
+                    .map_err(|_| "Session ID is not UTF-8".to_string())?;
+                let mut payload_len = [0u8; 4];
+                payload_len.copy_from_slice(take(4)?);
+                let payload = take(u32::from_be_bytes(payload_len) as usize)?.to_vec();
+                Ok(M::from_fields(
+                    session_id,
+                    sender,
+                    has_recipient.then_some(recipient),
+                    round,
+                    payload,
+                    u64::from_be_bytes(seq),
+                ))
+            }
+        }
+    }
+
+    /// Encode and decode `msg`, dropping it if either fails.
+    fn hop<M: Envelope>(self, msg: &M) -> Option<M> {
+        match self.encode(msg).and_then(|bytes| self.decode(&bytes)) {
+            Ok(msg) => Some(msg),
+            Err(e) => {
+                error!(
+                    "Dropping message the {} codec cannot carry: {}",
+                    self.name(),
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for BenchCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for BenchCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(BenchCodec::Json),
+            "binary" => Ok(BenchCodec::Binary),
+            _ => Err(format!("Unknown codec: {}", s)),
+        }
+    }
+}
+
+/// Relay envelope of the keygen and signing protocols.
+trait Envelope: Serialize + DeserializeOwned {
+    /// (session ID, sender, recipient, round, payload, sequence number)
+    fn fields(&self) -> (&str, u16, Option<u16>, u16, &[u8], u64);
+
+    fn from_fields(
+        session_id: String,
+        sender: u16,
+        recipient: Option<u16>,
+        round: u16,
+        payload: Vec<u8>,
+        seq: u64,
+    ) -> Self;
+}
+
+macro_rules! impl_envelope {
+    ($($message:ty),*) => {
+        $(
+            impl Envelope for $message {
+                fn fields(&self) -> (&str, u16, Option<u16>, u16, &[u8], u64) {
+                    (
+                        &self.session_id,
+                        self.sender,
+                        self.recipient,
+                        self.round,
+                        &self.payload,
+                        self.seq,
+                    )
+                }
+
+                fn from_fields(
+                    session_id: String,
+                    sender: u16,
+                    recipient: Option<u16>,
+                    round: u16,
+                    payload: Vec<u8>,
+                    seq: u64,
+                ) -> Self {
+                    Self {
+                        session_id,
+                        sender,
+                        recipient,
+                        round,
+                        payload,
+                        seq,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_envelope!(keygen::ProtocolMessage, signing::ProtocolMessage);
+
+/// Output format of the harness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BenchFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// What to benchmark and how often.
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    pub grid: Vec<BenchGridPoint>,
+    /// Messages signed per signing session
+    pub message_counts: Vec<usize>,
+    pub ciphersuites: Vec<FrostCiphersuite>,
+    pub codecs: Vec<BenchCodec>,
+    /// Runs per configuration
+    pub repetitions: usize,
+    pub format: BenchFormat,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            grid: vec![BenchGridPoint {
+                threshold: 2,
+                parties: 3,
+            }],
+            message_counts: vec![1],
+            ciphersuites: vec![FrostCiphersuite::Bitcoin],
+            codecs: vec![BenchCodec::Json],
+            repetitions: 10,
+            format: BenchFormat::Json,
+        }
+    }
+}
+
+/// Keygen among all parties; returns the key shares and party 0's run.
+fn bench_keygen(
+    point: BenchGridPoint,
+    ciphersuite: FrostCiphersuite,
+    codec: BenchCodec,
+    session_id: &str,
+) -> Result<(Vec<Vec<u8>>, RunReport), String> {
+    let options = KeygenOptions {
+        ciphersuite,
+        ..Default::default()
+    };
+    let results = run_parties_over(
+        point.parties,
+        |m: &keygen::ProtocolMessage| (m.sender, m.recipient),
+        |m| codec.hop(m),
+        |i, rx, tx| {
+            run_frost_keygen_with_benchmark(
+                i,
+                point.parties,
+                point.threshold,
+                session_id,
+                &options,
+                rx,
+                tx,
+                true,
+            )
+        },
+    );
+
+    let mut key_shares = Vec::with_capacity(results.len());
+    let mut run = None;
+    for result in results {
+        if !result.success {
+            return Err(result.error.unwrap_or_else(|| "Keygen failed".to_string()));
+        }
+        if run.is_none() {
+            run = result.benchmark.as_ref().map(RunReport::from_report);
+        }
+        key_shares.push(
+            result
+                .key_share_data
+                .ok_or("Keygen returned no key share")?,
+        );
+    }
+    Ok((
+        key_shares,
+        run.ok_or("Keygen returned no benchmark report")?,
+    ))
+}
+
+/// Sum the runs of consecutive sessions step by step, in first-recorded
+/// order.
+fn merge_runs(name: String, session_id: &str, runs: &[RunReport]) -> RunReport {
+    let mut steps: Vec<StepTiming> = Vec::new();
+    for step in runs.iter().flat_map(|run| &run.steps) {
+        match steps.iter_mut().find(|s| s.step == step.step) {
+            Some(total) => total.duration_secs += step.duration_secs,
+            None => steps.push(step.clone()),
+        }
+    }
+    // Keep the total last
+    if let Some(i) = steps.iter().position(|s| s.step == TOTAL_STEP) {
+        let total = steps.remove(i);
+        steps.push(total);
+    }
+    RunReport {
+        name,
+        party_index: 0,
+        session_id: session_id.to_string(),
+        steps,
+    }
+}
+
+/// Party 0's run, out of every signer's.
+fn party_0_run(runs: Vec<Result<RunReport, String>>) -> Result<RunReport, String> {
+    runs.into_iter()
+        .next()
+        .unwrap_or_else(|| Err("No signers".to_string()))
+}
+
+/// Sign `message_count` messages with the first `threshold` key shares;
+/// returns party 0's run.
+///
+/// Every suite runs the same protocol: Givre's full signing, untweaked, one
+/// session per message, with the steps of all sessions summed. Bitcoin's
+/// Taproot and batch entry points are not benchmarked here, so suites stay
+/// comparable.
+fn bench_signing(
+    key_shares: &[Vec<u8>],
+    threshold: u16,
+    message_count: usize,
+    ciphersuite: FrostCiphersuite,
+    codec: BenchCodec,
+    session_id: &str,
+) -> Result<RunReport, String> {
+    let signers: Vec<u16> = (0..threshold).collect();
+    let messages: Vec<[u8; 32]> = (0..message_count)
+        .map(|k| Sha256::digest(format!("frost-bench message {}", k)).into())
+        .collect();
+    let route = |m: &signing::ProtocolMessage| (m.sender, m.recipient);
+
+    let mut runs = Vec::with_capacity(message_count);
+    for (k, message) in messages.iter().enumerate() {
+        let message_session_id = format!("{}:{}", session_id, k);
+        let results = run_parties_over(
+            threshold,
+            route,
+            |m| codec.hop(m),
+            |i, rx, tx| {
+                let message_session_id = &message_session_id;
+                let key_share = &key_shares[usize::from(i)];
+                let signers = &signers;
+                async move {
+                    let r = run_frost_signing_with_ciphersuite_benchmark(
+                        i,
+                        signers,
+                        message_session_id,
+                        message,
+                        key_share,
+                        ciphersuite,
+                        rx,
+                        tx,
+                        true,
+                    )
+                    .await;
+                    if r.success {
+                        r.benchmark
+                            .as_ref()
+                            .map(RunReport::from_report)
+                            .ok_or_else(|| "Signing returned no benchmark report".to_string())
+                    } else {
+                        Err(r.error.unwrap_or_else(|| "Signing failed".to_string()))
+                    }
+                }
+            },
+        );
+        runs.push(party_0_run(results)?);
+    }
+    Ok(merge_runs(
+        format!("FROST-Signing-{}", ciphersuite),
+        session_id,
+        &runs,
+    ))
+}
+
+/// Run every configuration `config.repetitions` times and aggregate.
+///
+/// Returns one keygen aggregate per ciphersuite, codec and grid point, and
+/// one signing aggregate per message count.
+pub fn run_frost_bench(config: &BenchConfig) -> Result<Vec<AggregateReport>, String> {
+    let mut reports = Vec::new();
+    for &ciphersuite in &config.ciphersuites {
+        for &codec in &config.codecs {
+            for &point in &config.grid {
+                let label = format!(
+                    "{} {}-of-{} {}",
+                    ciphersuite, point.threshold, point.parties, codec
+                );
+                let mut keygen_runs = Vec::with_capacity(config.repetitions);
+                let mut signing_runs: BTreeMap<usize, Vec<RunReport>> = BTreeMap::new();
+
+                for rep in 0..config.repetitions {
+                    let session_id = format!("frost-bench/{}/{}", label.replace(' ', "/"), rep);
+                    let (key_shares, run) = bench_keygen(point, ciphersuite, codec, &session_id)?;
+                    keygen_runs.push(run);
+
+                    for &count in &config.message_counts {
+                        let run = bench_signing(
+                            &key_shares,
+                            point.threshold,
+                            count,
+                            ciphersuite,
+                            codec,
+                            &format!("{}/sign/{}", session_id, count),
+                        )?;
+                        signing_runs.entry(count).or_default().push(run);
+                    }
+                }
+
+                reports.push(AggregateReport::from_runs(
+                    &format!("FROST-Keygen {}", label),
+                    &keygen_runs,
+                ));
+                for (count, runs) in signing_runs {
+                    reports.push(AggregateReport::from_runs(
+                        &format!("FROST-Signing {} x{}", label, count),
+                        &runs,
+                    ));
+                }
+            }
+        }
+    }
+    Ok(reports)
+}
+
+/// Render aggregates in `format`.
+pub fn format_bench_reports(
+    reports: &[AggregateReport],
+    format: BenchFormat,
+) -> Result<String, String> {
+    match format {
+        BenchFormat::Json => serde_json::to_string_pretty(reports)
+            .map_err(|e| format!("Failed to serialize reports: {}", e)),
+        BenchFormat::Csv => {
+            let mut csv = String::new();
+            for (i, report) in reports.iter().enumerate() {
+                // One header for all reports
+                csv.extend(
+                    report
+                        .to_csv()
+                        .lines()
+                        .skip(usize::from(i > 0))
+                        .map(|line| format!("{}\n", line)),
+                );
+            }
+            Ok(csv)
+        }
+    }
+}
+
+/// Operation counts of keygen and signing for every ciphersuite and grid
+/// point of `config`, counted from real runs.
+pub fn profile_bench_ops(config: &BenchConfig) -> Result<Vec<OpProfile>, String> {
+    let mut profiles = Vec::new();
+    for &ciphersuite in &config.ciphersuites {
+        let options = KeygenOptions {
+            ciphersuite,
+            ..Default::default()
+        };
+        for point in &config.grid {
+            profiles.push(profile_keygen_ops(
+                point.threshold,
+                point.parties,
+                &options,
+            )?);
+            profiles.push(profile_signing_ops(
+                point.threshold,
+                point.parties,
+                ciphersuite,
+            )?);
+        }
+    }
+    Ok(profiles)
+}
+
+/// Render operation counts in `format`.
+pub fn format_op_profiles(profiles: &[OpProfile], format: BenchFormat) -> Result<String, String> {
+    match format {
+        BenchFormat::Json => serde_json::to_string_pretty(profiles)
+            .map_err(|e| format!("Failed to serialize profiles: {}", e)),
+        BenchFormat::Csv => {
+            let mut csv = format!("{}\n", OpProfile::CSV_HEADER);
+            csv.extend(profiles.iter().map(OpProfile::csv_rows));
+            Ok(csv)
+        }
+    }
+}
+
+fn parse_list<T: std::str::FromStr>(flag: &str, value: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|v| {
+            v.trim()
+                .parse()
+                .map_err(|_| format!("Invalid value for {}: {}", flag, v))
+        })
+        .collect()
+}
+
+/// Parse `frost-bench` run arguments.
+pub fn parse_bench_args(args: &[String]) -> Result<BenchConfig, String> {
+    let mut config = BenchConfig::default();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", flag))?;
+        match flag.as_str() {
+            "--grid" => config.grid = parse_list(flag, value)?,
+            "--messages" => config.message_counts = parse_list(flag, value)?,
+            "--suites" => config.ciphersuites = parse_list(flag, value)?,
+            "--codecs" => config.codecs = parse_list(flag, value)?,
+            "--reps" => {
+                config.repetitions = value
+                    .parse()
+                    .map_err(|_| format!("Invalid value for --reps: {}", value))?
+            }
+            "--format" => {
+                config.format = match value.as_str() {
+                    "json" => BenchFormat::Json,
+                    "csv" => BenchFormat::Csv,
+                    _ => return Err(format!("Unknown format: {}", value)),
+                }
+            }
+            _ => return Err(format!("Unknown option: {}", flag)),
+        }
+    }
+    if config.repetitions == 0 || config.message_counts.contains(&0) {
+        return Err("Repetitions and message counts must be positive".to_string());
+    }
+    Ok(config)
+}
+
+fn print_usage() {
+    eprintln!("Usage: frost-bench [--grid 2-of-3,3-of-5] [--messages 1,10]");
+    eprintln!("                   [--suites bitcoin,ed25519] [--codecs json,binary]");
+    eprintln!("                   [--reps 10] [--format json|csv]");
+    eprintln!("       frost-bench compare <baseline> <current> [--clock-ns 10]");
+    eprintln!("       frost-bench ops [--grid 2-of-3] [--suites ed25519] [--format json|csv]");
+    eprintln!();
+    eprintln!("Runs keygen and signing in-process and prints per-step min/median/p95/max.");
+    eprintln!("Every suite signs each message with Givre's full signing protocol.");
+    eprintln!("compare diffs two report files, or a report against the log of a");
+    eprintln!("Verilog testbench run. ops counts the scalar multiplications, point");
+    eprintln!("additions, scalar inversions and hashes of each round in real runs.");
+}
+
+/// Entry point of the `frost-bench` binary. Returns the process exit code.
+pub fn run_frost_bench_cli(args: &[String]) -> i32 {
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        print_usage();
+        return 2;
+    }
+
+    if args.first().map(String::as_str) == Some("compare") {
+        let clock_period_ns = match args.get(3..) {
+            Some([flag, ns]) if flag == "--clock-ns" => match ns.parse() {
+                Ok(ns) => ns,
+                Err(_) => {
+                    eprintln!("frost-bench: invalid clock period: {}", ns);
+                    return 2;
+                }
+            },
+            Some([]) => TESTBENCH_CLOCK_PERIOD_NS,
+            _ => {
+                print_usage();
+                return 2;
+            }
+        };
+        return match compare_report_files(&args[1], &args[2], clock_period_ns) {
+            Ok(diff) => {
+                print!("{}", diff);
+                0
+            }
+            Err(e) => {
+                eprintln!("frost-bench: {}", e);
+                2
+            }
+        };
+    }
+
+    let ops = args.first().map(String::as_str) == Some("ops");
+    let config = match parse_bench_args(if ops { &args[1..] } else { args }) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("frost-bench: {}", e);
+            print_usage();
+            return 2;
+        }
+    };
+    let output = if ops {
+        profile_bench_ops(&config).and_then(|profiles| format_op_profiles(&profiles, config.format))
+    } else {
+        run_frost_bench(&config).and_then(|reports| format_bench_reports(&reports, config.format))
+    };
+    match output {
+        Ok(output) => {
+            print!("{}", output);
+            0
+        }
+        Err(e) => {
+            eprintln!("frost-bench: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bench_args() {
+        let args: Vec<String> = [
+            "--grid",
+            "2-of-3,3-of-5",
+            "--suites",
+            "ed25519",
+            "--codecs",
+            "json,binary",
+            "--reps",
+            "3",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let config = parse_bench_args(&args).unwrap();
+        assert_eq!(
+            config.grid,
+            vec![
+                BenchGridPoint {
+                    threshold: 2,
+                    parties: 3
+                },
+                BenchGridPoint {
+                    threshold: 3,
+                    parties: 5
+                },
+            ]
+        );
+        assert_eq!(config.ciphersuites, vec![FrostCiphersuite::Ed25519]);
+        assert_eq!(config.codecs, vec![BenchCodec::Json, BenchCodec::Binary]);
+        assert_eq!(config.message_counts, vec![1]);
+        assert_eq!(config.repetitions, 3);
+
+        assert!("4-of-3".parse::<BenchGridPoint>().is_err());
+        assert!(parse_bench_args(&["--reps".to_string()]).is_err());
+        assert!(parse_bench_args(&["--codecs".to_string(), "xml".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_bench_runs_2_of_2_once() {
+        let config = BenchConfig {
+            grid: vec!["2-of-2".parse().unwrap()],
+            message_counts: vec![1, 2],
+            ciphersuites: vec![FrostCiphersuite::Bitcoin, FrostCiphersuite::Ed25519],
+            codecs: vec![BenchCodec::Json, BenchCodec::Binary],
+            repetitions: 1,
+            format: BenchFormat::Json,
+        };
+        let reports = run_frost_bench(&config).unwrap();
+
+        // One keygen and two signing aggregates per suite and codec
+        assert_eq!(reports.len(), 2 * 2 * 3);
+        for report in &reports {
+            assert_eq!(report.runs, 1);
+            assert!(
+                report.step(TOTAL_STEP).is_some(),
+                "{} has no total",
+                report.name
+            );
+        }
+
+        // Every suite runs the same protocol, reported per step and summed
+        // over the messages
+        for suite in ["bitcoin", "ed25519"] {
+            let name = format!("FROST-Signing {} 2-of-2 binary x2", suite);
+            let signing = reports.iter().find(|r| r.name == name).unwrap();
+            assert!(signing.step("1. Deserialize key_share").is_some());
+            assert!(signing.step("3. MPC signing protocol").is_some());
+            assert!(signing.step("3.1 Round 1: local compute").is_some());
+        }
+    }
+}
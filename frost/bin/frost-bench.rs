@@ -0,0 +1,18 @@
+//! `frost-bench`: in-process keygen and signing benchmarks.
+//!
+//! This tree has no manifest. In a crate whose library mounts `frost/` as
+//! `frost_hardware::frost`, register it next to the library as
+//!
+//! ```toml
+//! [[bin]]
+//! name = "frost-bench"
+//! path = "frost/bin/frost-bench.rs"
+//! ```
+//!
+//! Exits with 0 on success, 1 if a benchmark run fails and 2 on usage or
+//! input errors.
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(frost_hardware::frost::run_frost_bench_cli(&args));
+}
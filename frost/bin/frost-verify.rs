@@ -0,0 +1,18 @@
+//! `frost-verify`: offline verifier for FROST keygen and signing transcripts.
+//!
+//! This tree has no manifest. In a crate whose library mounts `frost/` as
+//! `frost_hardware::frost`, register it next to the library as
+//!
+//! ```toml
+//! [[bin]]
+//! name = "frost-verify"
+//! path = "frost/bin/frost-verify.rs"
+//! ```
+//!
+//! Exits with 0 if every check passes, 1 if any fails and 2 on usage or
+//! input errors.
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(frost_hardware::frost::run_frost_verify_cli(&args));
+}
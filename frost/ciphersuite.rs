@@ -0,0 +1,534 @@
+//! Runtime selection between Givre's FROST ciphersuites.
+//!
+//! The Taproot flows are built on the Bitcoin (BIP-340) ciphersuite. Other
+//! chains need plain secp256k1, Ed25519 or Ristretto255 FROST signatures, so
+//! keygen and signing can also run under any of those suites. Key shares are
+//! stored in an envelope recording their ciphersuite, and loading a share
+//! under a different suite is refused.
+//!
+//! The envelope changes the stored key share format: keygen now writes
+//! `{"ciphersuite": "bitcoin", "key_share": {...}}` instead of the bare Givre
+//! key share. Bare shares written before still load as Bitcoin shares, and
+//! [`migrate_key_share`] rewrites them in the new format. Releases without
+//! ciphersuite support cannot read enveloped shares.
+
+use std::time::{Duration, Instant};
+
+use async_channel::{Receiver, Sender};
+use rand::rngs::OsRng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use givre::ciphersuite::{Bitcoin, Ciphersuite, Ed25519, Ristretto255, Secp256k1};
+
+use super::keygen::KeygenOptions;
+use super::op_count::{self, Counting};
+use super::signing::{ChannelSink, ChannelStream, FrostKeyShare, ProtocolMessage};
+use super::timing::{TransportLog, TransportTimings};
+use crate::bench::{BenchmarkRecorder, BenchmarkReport};
+
+/// FROST ciphersuite a key share belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrostCiphersuite {
+    /// secp256k1 with BIP-340 x-only keys and challenges (Taproot)
+    #[default]
+    Bitcoin,
+    /// secp256k1 with RFC 9591 challenges and compressed keys
+    Secp256k1,
+    /// Ed25519 (RFC 8032 compatible signatures)
+    Ed25519,
+    /// Ristretto255
+    Ristretto255,
+}
+
+impl FrostCiphersuite {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FrostCiphersuite::Bitcoin => "bitcoin",
+            FrostCiphersuite::Secp256k1 => "secp256k1",
+            FrostCiphersuite::Ed25519 => "ed25519",
+            FrostCiphersuite::Ristretto255 => "ristretto255",
+        }
+    }
+}
+
+impl std::fmt::Display for FrostCiphersuite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for FrostCiphersuite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bitcoin" | "bip340" => Ok(FrostCiphersuite::Bitcoin),
+            "secp256k1" => Ok(FrostCiphersuite::Secp256k1),
+            "ed25519" => Ok(FrostCiphersuite::Ed25519),
+            "ristretto255" => Ok(FrostCiphersuite::Ristretto255),
+            _ => Err(format!("Unknown FROST ciphersuite: {}", s)),
+        }
+    }
+}
+
+/// Stored form of a key share: the Givre key share tagged with its suite
+/// and the options it was generated with.
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyShareEnvelope {
+    ciphersuite: FrostCiphersuite,
+    /// Absent in envelopes written before keygen options were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keygen_options: Option<KeygenOptions>,
+    key_share: serde_json::Value,
+}
+
+/// Serialize a Givre key share together with the keygen `options` (and so
+/// the ciphersuite) it was generated with.
+pub fn wrap_key_share<T: Serialize>(
+    options: &KeygenOptions,
+    key_share: &T,
+) -> Result<Vec<u8>, String> {
+    let key_share = serde_json::to_value(key_share)
+        .map_err(|e| format!("Failed to serialize key share: {}", e))?;
+    serde_json::to_vec(&KeyShareEnvelope {
+        ciphersuite: options.ciphersuite,
+        keygen_options: Some(options.clone()),
+        key_share,
+    })
+    .map_err(|e| format!("Failed to serialize key share: {}", e))
+}
+
+/// Ciphersuite recorded in stored key share data.
+///
+/// Bare key shares written before ciphersuites were recorded are Bitcoin
+/// shares. Data that is neither is refused.
+pub fn key_share_ciphersuite(key_share_data: &[u8]) -> Result<FrostCiphersuite, String> {
+    match serde_json::from_slice::<KeyShareEnvelope>(key_share_data) {
+        Ok(envelope) => Ok(envelope.ciphersuite),
+        Err(e) => serde_json::from_slice::<FrostKeyShare>(key_share_data)
+            .map(|_| FrostCiphersuite::Bitcoin)
+            .map_err(|_| format!("Not a FROST key share: {}", e)),
+    }
+}
+
+/// Keygen options recorded in stored key share data.
+///
+/// Bare key shares predate keygen options and were generated with the
+/// defaults. Returns `None` for enveloped shares written before the options
+/// were recorded.
+pub fn key_share_options(key_share_data: &[u8]) -> Result<Option<KeygenOptions>, String> {
+    match serde_json::from_slice::<KeyShareEnvelope>(key_share_data) {
+        Ok(envelope) => Ok(envelope.keygen_options),
+        Err(_) => key_share_ciphersuite(key_share_data).map(|_| Some(KeygenOptions::default())),
+    }
+}
+
+/// Rewrite a bare key share from before ciphersuites were recorded in the
+/// enveloped format. Enveloped shares are returned unchanged.
+pub fn migrate_key_share(key_share_data: &[u8]) -> Result<Vec<u8>, String> {
+    if serde_json::from_slice::<KeyShareEnvelope>(key_share_data).is_ok() {
+        return Ok(key_share_data.to_vec());
+    }
+    let key_share: FrostKeyShare = serde_json::from_slice(key_share_data)
+        .map_err(|e| format!("Not a bare Bitcoin key share: {}", e))?;
+    wrap_key_share(&KeygenOptions::default(), &key_share)
+}
+
+/// Load a key share for use under `expected`, refusing shares of any other suite.
+pub fn load_key_share<T: DeserializeOwned>(
+    key_share_data: &[u8],
+    expected: FrostCiphersuite,
+) -> Result<T, String> {
+    let key_share = match serde_json::from_slice::<KeyShareEnvelope>(key_share_data) {
+        Ok(envelope) if envelope.ciphersuite != expected => {
+            return Err(format!(
+                "Key share belongs to the {} ciphersuite and cannot be used with {}",
+                envelope.ciphersuite, expected
+            ));
+        }
+        Ok(envelope) => serde_json::from_value(envelope.key_share),
+        // Untagged shares predate ciphersuite support and are Bitcoin shares
+        Err(_) if expected == FrostCiphersuite::Bitcoin => serde_json::from_slice(key_share_data),
+        Err(e) => {
+            return Err(format!(
+                "Key share has no ciphersuite tag and cannot be used with {}: {}",
+                expected, e
+            ));
+        }
+    };
+    key_share.map_err(|e| format!("Key share deserialization error: {}", e))
+}
+
+/// Result of FROST signing under a selectable ciphersuite.
+#[derive(Debug)]
+pub struct FrostSuiteSigningResult {
+    pub success: bool,
+    pub ciphersuite: FrostCiphersuite,
+    /// Signature in the suite's standard encoding (R || z)
+    pub signature: Option<Vec<u8>>,
+    /// Group public key in the suite's standard encoding: x-only (32 bytes)
+    /// for Bitcoin, compressed SEC1 for secp256k1, RFC 8032 for Ed25519
+    pub public_key: Option<Vec<u8>>,
+    /// Error message if failed
+    pub error: Option<String>,
+    /// Duration of the protocol
+    pub duration_secs: f64,
+    /// Detailed benchmark report (if benchmarking enabled)
+    pub benchmark: Option<BenchmarkReport>,
+}
+
+/// Run FROST threshold signing of `message` under `ciphersuite`.
+///
+/// The key share must have been generated under the same ciphersuite. For
+/// Taproot spends use [`super::signing::run_frost_signing_with_options`],
+/// which applies the BIP-341 tweak.
+///
+/// Nonces are generated inside this session and dropped after their single
+/// use, so there is nothing to record in a [`super::nonce_ledger::NonceLedger`].
+#[allow(clippy::too_many_arguments)]
+pub async fn run_frost_signing_with_ciphersuite(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    message: &[u8],
+    key_share_data: &[u8],
+    ciphersuite: FrostCiphersuite,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostSuiteSigningResult {
+    run_frost_signing_with_ciphersuite_benchmark(
+        party_index,
+        parties_at_keygen,
+        session_id,
+        message,
+        key_share_data,
+        ciphersuite,
+        incoming_rx,
+        outgoing_tx,
+        false,
+    )
+    .await
+}
+
+/// Run FROST threshold signing of `message` under `ciphersuite` with
+/// optional benchmarking.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_frost_signing_with_ciphersuite_benchmark(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    message: &[u8],
+    key_share_data: &[u8],
+    ciphersuite: FrostCiphersuite,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+    enable_benchmark: bool,
+) -> FrostSuiteSigningResult {
+    let start = std::time::Instant::now();
+
+    info!("========================================");
+    info!("  FROST SIGNING STARTING ({})", ciphersuite);
+    info!("========================================");
+    info!("Party index: {}", party_index);
+    info!("Parties at keygen: {:?}", parties_at_keygen);
+    info!("Session ID: {}", session_id);
+
+    let mut recorder = enable_benchmark.then(|| {
+        BenchmarkRecorder::new(
+            &format!("FROST-Signing-{}", ciphersuite),
+            party_index,
+            session_id,
+        )
+    });
+    let result = match ciphersuite {
+        FrostCiphersuite::Bitcoin => {
+            sign_with::<Bitcoin>(
+                party_index,
+                parties_at_keygen,
+                session_id,
+                message,
+                key_share_data,
+                ciphersuite,
+                incoming_rx,
+                outgoing_tx,
+                recorder.as_mut(),
+            )
+            .await
+        }
+        FrostCiphersuite::Secp256k1 => {
+            sign_with::<Secp256k1>(
+                party_index,
+                parties_at_keygen,
+                session_id,
+                message,
+                key_share_data,
+                ciphersuite,
+                incoming_rx,
+                outgoing_tx,
+                recorder.as_mut(),
+            )
+            .await
+        }
+        FrostCiphersuite::Ed25519 => {
+            sign_with::<Ed25519>(
+                party_index,
+                parties_at_keygen,
+                session_id,
+                message,
+                key_share_data,
+                ciphersuite,
+                incoming_rx,
+                outgoing_tx,
+                recorder.as_mut(),
+            )
+            .await
+        }
+        FrostCiphersuite::Ristretto255 => {
+            sign_with::<Ristretto255>(
+                party_index,
+                parties_at_keygen,
+                session_id,
+                message,
+                key_share_data,
+                ciphersuite,
+                incoming_rx,
+                outgoing_tx,
+                recorder.as_mut(),
+            )
+            .await
+        }
+    };
+
+    let benchmark = recorder.map(|mut rec| {
+        rec.complete();
+        let report = rec.report();
+        report.log();
+        report
+    });
+
+    match result {
+        Ok((signature, public_key)) => {
+            info!(
+                "FROST {} signing completed in {:.2}s",
+                ciphersuite,
+                start.elapsed().as_secs_f64()
+            );
+            info!("Signature: {}", hex::encode(&signature));
+            FrostSuiteSigningResult {
+                success: true,
+                ciphersuite,
+                signature: Some(signature),
+                public_key: Some(public_key),
+                error: None,
+                duration_secs: start.elapsed().as_secs_f64(),
+                benchmark,
+            }
+        }
+        Err(e) => {
+            error!("FROST {} signing failed: {}", ciphersuite, e);
+            FrostSuiteSigningResult {
+                success: false,
+                ciphersuite,
+                signature: None,
+                public_key: None,
+                error: Some(e),
+                duration_secs: start.elapsed().as_secs_f64(),
+                benchmark,
+            }
+        }
+    }
+}
+
+/// Sign under ciphersuite `C`, or under its counting wrapper while an
+/// operation profile is being taken.
+#[allow(clippy::too_many_arguments)]
+async fn sign_with<C: Ciphersuite>(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    message: &[u8],
+    key_share_data: &[u8],
+    ciphersuite: FrostCiphersuite,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+    recorder: Option<&mut BenchmarkRecorder>,
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+    if op_count::is_counting() {
+        sign::<Counting<C>>(
+            party_index,
+            parties_at_keygen,
+            session_id,
+            message,
+            key_share_data,
+            ciphersuite,
+            incoming_rx,
+            outgoing_tx,
+            recorder,
+        )
+        .await
+    } else {
+        sign::<C>(
+            party_index,
+            parties_at_keygen,
+            session_id,
+            message,
+            key_share_data,
+            ciphersuite,
+            incoming_rx,
+            outgoing_tx,
+            recorder,
+        )
+        .await
+    }
+}
+
+/// Sign under ciphersuite `C`, returning the encoded signature and public key.
+#[allow(clippy::too_many_arguments)]
+async fn sign<C: Ciphersuite>(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    message: &[u8],
+    key_share_data: &[u8],
+    ciphersuite: FrostCiphersuite,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+    mut recorder: Option<&mut BenchmarkRecorder>,
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let mut record = |step: &str, elapsed: Duration| {
+        if let Some(rec) = recorder.as_mut() {
+            rec.record_step(step, elapsed);
+        }
+    };
+
+    let step_start = Instant::now();
+    let key_share: givre::KeyShare<C::Curve> = load_key_share(key_share_data, ciphersuite)?;
+    record("1. Deserialize key_share", step_start.elapsed());
+
+    let step_start = Instant::now();
+    let transport_log = TransportLog::default();
+    let party = round_based::MpcParty::connected((
+        Box::pin(ChannelStream::new(incoming_rx).with_timings(transport_log.clone())),
+        Box::pin(
+            ChannelSink::new(outgoing_tx, session_id, party_index)
+                .with_timings(transport_log.clone()),
+        ),
+    ));
+    record("2. Protocol setup (channels, party)", step_start.elapsed());
+
+    let step_start = Instant::now();
+    if let Ok(mut timings) = transport_log.lock() {
+        *timings = TransportTimings::new();
+    }
+    let signature = givre::signing::<C>(party_index, &key_share, parties_at_keygen, message)
+        .sign(&mut OsRng, party)
+        .await
+        .map_err(|e| format!("Protocol error: {:?}", e))?;
+    let protocol_end = Instant::now();
+    record("3. MPC signing protocol", step_start.elapsed());
+    if let Ok(timings) = transport_log.lock() {
+        timings.record(3, protocol_end, &mut record);
+    }
+
+    let step_start = Instant::now();
+    let mut signature_bytes = C::serialize_normalized_point(&signature.r)
+        .as_ref()
+        .to_vec();
+    signature_bytes.extend_from_slice(C::serialize_scalar(&signature.z).as_ref());
+    let public_key =
+        C::serialize_normalized_point(&C::normalize_point(key_share.shared_public_key()))
+            .as_ref()
+            .to_vec();
+    record("4. Encode signature", step_start.elapsed());
+    Ok((signature_bytes, public_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::local;
+    use super::super::signing::SchnorrSignature;
+    use super::*;
+
+    #[test]
+    fn test_load_key_share_refuses_other_ciphersuite() {
+        let share = serde_json::json!({ "i": 0 });
+        let options = KeygenOptions {
+            ciphersuite: FrostCiphersuite::Ed25519,
+            ..Default::default()
+        };
+        let data = wrap_key_share(&options, &share).unwrap();
+        assert_eq!(key_share_ciphersuite(&data), Ok(FrostCiphersuite::Ed25519));
+
+        let loaded: serde_json::Value = load_key_share(&data, FrostCiphersuite::Ed25519).unwrap();
+        assert_eq!(loaded, share);
+        assert!(load_key_share::<serde_json::Value>(&data, FrostCiphersuite::Bitcoin).is_err());
+
+        // Untagged data loads as a Bitcoin share, but only a real key share
+        // is recognized as one
+        let legacy = serde_json::to_vec(&share).unwrap();
+        assert!(key_share_ciphersuite(&legacy).is_err());
+        assert!(load_key_share::<serde_json::Value>(&legacy, FrostCiphersuite::Bitcoin).is_ok());
+        assert!(load_key_share::<serde_json::Value>(&legacy, FrostCiphersuite::Ed25519).is_err());
+    }
+
+    #[test]
+    fn test_bare_key_shares_load_and_migrate() {
+        let data = local::key_shares(2, 2).remove(0);
+        let envelope: KeyShareEnvelope = serde_json::from_slice(&data).unwrap();
+        let bare = serde_json::to_vec(&envelope.key_share).unwrap();
+
+        assert_eq!(key_share_ciphersuite(&bare), Ok(FrostCiphersuite::Bitcoin));
+        let key_share: FrostKeyShare = load_key_share(&bare, FrostCiphersuite::Bitcoin).unwrap();
+        assert!(load_key_share::<FrostKeyShare>(&bare, FrostCiphersuite::Secp256k1).is_err());
+
+        let migrated = migrate_key_share(&bare).unwrap();
+        assert_eq!(
+            key_share_ciphersuite(&migrated),
+            Ok(FrostCiphersuite::Bitcoin)
+        );
+        let reloaded: FrostKeyShare = load_key_share(&migrated, FrostCiphersuite::Bitcoin).unwrap();
+        assert_eq!(
+            serde_json::to_value(&reloaded).unwrap(),
+            serde_json::to_value(&key_share).unwrap()
+        );
+        assert_eq!(key_share_options(&bare), Ok(Some(KeygenOptions::default())));
+        assert_eq!(
+            key_share_options(&migrated),
+            Ok(Some(KeygenOptions::default()))
+        );
+        assert_eq!(migrate_key_share(&migrated).unwrap(), migrated);
+        assert!(migrate_key_share(b"{}").is_err());
+    }
+
+    #[test]
+    fn test_bitcoin_suite_returns_x_only_key() {
+        let key_shares = local::key_shares(2, 2);
+        let message = [0x42u8; 32];
+        let results = local::run_parties(
+            2,
+            |m: &ProtocolMessage| (m.sender, m.recipient),
+            |i, rx, tx| {
+                run_frost_signing_with_ciphersuite(
+                    i,
+                    &[0, 1],
+                    "suite",
+                    &message,
+                    &key_shares[usize::from(i)],
+                    FrostCiphersuite::Bitcoin,
+                    rx,
+                    tx,
+                )
+            },
+        );
+        for result in results {
+            assert!(result.success, "signing failed: {:?}", result.error);
+            let public_key = result.public_key.unwrap();
+            assert_eq!(public_key.len(), 32);
+            let signature = SchnorrSignature::try_from(&result.signature.unwrap()[..]).unwrap();
+            assert!(signature.verify(&public_key, &message).is_ok());
+        }
+    }
+}
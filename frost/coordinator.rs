@@ -0,0 +1,445 @@
+//! Coordinator-based FROST signing (hub-and-spoke).
+//!
+//! In Givre's full-signing flow every signer broadcasts to every other
+//! signer. Here one untrusted coordinator sits in the middle: it collects
+//! nonce commitments, sends every signer the signing package, collects
+//! partial signatures, verifies each share and aggregates. Signers only ever
+//! talk to the coordinator, and the coordinator holds no secret material.
+//!
+//! Rounds:
+//! 1. signer -> coordinator: nonce commitments
+//! 2. coordinator -> signer: signing package
+//! 3. signer -> coordinator: partial signature
+//!
+//! Unlike the broadcast flows, which number parties on the relay by their
+//! position in `parties_at_keygen`, every party here is addressed by its
+//! index at keygen (and the coordinator by [`COORDINATOR_INDEX`]). Signers
+//! are told nothing about the signing set before the package arrives and
+//! substitutes can join a retry, so the only stable number a signer has is
+//! its own key share index. `blamed_parties` uses keygen indices in every
+//! flow.
+
+use async_channel::{Receiver, Sender};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use super::ciphersuite::{load_key_share, FrostCiphersuite};
+use super::rounds::{
+    aggregate, commit, sign_share, signing_tweak, verify_shares, FrostKeyInfo, PartialSignature,
+    PublicCommitments, RoundTransport,
+};
+use super::signing::{
+    verifying_key, FrostKeyShare, FrostSigningResult, ProtocolMessage, SigningMode, SigningOptions,
+};
+use super::taproot::TaprootTweak;
+use super::transcript::SigningTranscript;
+use crate::bench::BenchmarkRecorder;
+
+/// Party index the coordinator uses on the relay.
+pub const COORDINATOR_INDEX: u16 = u16::MAX;
+
+/// Everything a signer needs to produce its partial signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningPackage {
+    pub message_hash: [u8; 32],
+    pub mode: SigningMode,
+    pub taproot_tweak: TaprootTweak,
+    /// Commitments of every signer, keyed by index at keygen
+    pub commitments: Vec<(u16, PublicCommitments)>,
+}
+
+/// Result of a signer's participation in coordinated signing.
+#[derive(Debug)]
+pub struct FrostSignerResult {
+    pub success: bool,
+    /// Error message if failed
+    pub error: Option<String>,
+    /// Duration of the protocol
+    pub duration_secs: f64,
+}
+
+/// Serialize the public key info of a key share for the coordinator.
+///
+/// The result contains no secret material.
+pub fn export_key_info(key_share_data: &[u8]) -> Result<Vec<u8>, String> {
+    let key_share: FrostKeyShare = load_key_share(key_share_data, FrostCiphersuite::Bitcoin)?;
+    let key_info: &FrostKeyInfo = key_share.as_ref();
+    serde_json::to_vec(key_info).map_err(|e| format!("Failed to serialize key info: {}", e))
+}
+
+/// Replace blamed signers with parties from `available` that are not yet signing.
+///
+/// Returns `None` if there are not enough substitutes left.
+pub fn substitute_signers(signers: &[u16], blamed: &[u16], available: &[u16]) -> Option<Vec<u16>> {
+    let mut substitutes = available
+        .iter()
+        .filter(|p| !signers.contains(p) && !blamed.contains(p));
+    let mut next = signers
+        .iter()
+        .map(|&p| {
+            if blamed.contains(&p) {
+                substitutes.next().copied()
+            } else {
+                Some(p)
+            }
+        })
+        .collect::<Option<Vec<u16>>>()?;
+    next.sort_unstable();
+    Some(next)
+}
+
+/// Check that `signers` are distinct keygen indices of `key_info`, in order.
+fn check_signers(key_info: &FrostKeyInfo, signers: &[u16]) -> Result<(), String> {
+    let n = key_info.public_shares.len();
+    if signers.iter().any(|&j| usize::from(j) >= n) || !signers.windows(2).all(|w| w[0] < w[1]) {
+        return Err(format!(
+            "Signers {:?} are not distinct keygen indices of a {}-party key",
+            signers, n
+        ));
+    }
+    Ok(())
+}
+
+/// Run the coordinator side of FROST signing.
+///
+/// `signers` are the keygen indices of the participating signers, which
+/// are also their party indices on the relay, and
+/// `key_info_data` is the output of [`export_key_info`]. If any partial
+/// signature is invalid, signing fails and the offending signers are listed
+/// in `blamed_parties`; retry with [`substitute_signers`].
+#[allow(clippy::too_many_arguments)]
+pub async fn run_frost_coordinator(
+    signers: &[u16],
+    session_id: &str,
+    message_hash: &[u8; 32],
+    key_info_data: &[u8],
+    options: &SigningOptions,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostSigningResult {
+    let start = std::time::Instant::now();
+    let mut recorder = BenchmarkRecorder::new("FROST-Coordinator", COORDINATOR_INDEX, session_id);
+
+    info!("========================================");
+    info!("  FROST COORDINATOR STARTING");
+    info!("========================================");
+    info!("Signers: {:?}", signers);
+    info!("Session ID: {}", session_id);
+    info!("Message hash: {}", hex::encode(message_hash));
+
+    let mut blamed_parties = Vec::new();
+    let result: Result<SigningTranscript, String> = async {
+        let key_info: FrostKeyInfo = serde_json::from_slice(key_info_data)
+            .map_err(|e| format!("Key info deserialization error: {}", e))?;
+        check_signers(&key_info, signers)?;
+        let output_key = verifying_key(&key_info, options)?;
+        let tweak = signing_tweak(&key_info, options.mode, options.taproot_tweak)?;
+        let mut transport =
+            RoundTransport::new(session_id, COORDINATOR_INDEX, incoming_rx, outgoing_tx);
+
+        // Round 1: collect commitments
+        let step_start = std::time::Instant::now();
+        let commitments: Vec<(u16, PublicCommitments)> = transport
+            .collect::<PublicCommitments>(1, signers)
+            .await?
+            .into_iter()
+            .collect();
+        recorder.record_step("1. Collect commitments", step_start.elapsed());
+
+        // Round 2: distribute the signing package
+        let step_start = std::time::Instant::now();
+        let package = SigningPackage {
+            message_hash: *message_hash,
+            mode: options.mode,
+            taproot_tweak: options.taproot_tweak,
+            commitments: commitments.clone(),
+        };
+        for &signer in signers {
+            transport.send(Some(signer), 2, &package).await?;
+        }
+        recorder.record_step("2. Distribute signing package", step_start.elapsed());
+
+        // Round 3: collect and verify partial signatures
+        let step_start = std::time::Instant::now();
+        let partials = transport.collect::<PartialSignature>(3, signers).await?;
+        let shares: Vec<_> = commitments
+            .iter()
+            .map(|(signer, c)| (*signer, c.clone(), partials[signer].clone()))
+            .collect();
+        // Signers are untrusted, so every share is checked before aggregation
+        if let Err(invalid) = verify_shares(&key_info, &shares, message_hash, tweak) {
+            blamed_parties = invalid.parties.clone();
+            return Err(invalid.to_string());
+        }
+        recorder.record_step(
+            "3. Collect and verify partial signatures",
+            step_start.elapsed(),
+        );
+
+        let step_start = std::time::Instant::now();
+        let signature = aggregate(&key_info, &shares, message_hash, tweak)?;
+        signature
+            .verify(&output_key, message_hash)
+            .map_err(|e| format!("Aggregated signature is invalid: {}", e))?;
+        recorder.record_step("4. Aggregate signature", step_start.elapsed());
+
+        Ok(SigningTranscript::new(
+            session_id,
+            signers,
+            message_hash,
+            &key_info,
+            options,
+            signature,
+            output_key,
+        ))
+    }
+    .await;
+
+    let benchmark = if options.enable_benchmark {
+        recorder.complete();
+        let report = recorder.report();
+        report.log();
+        Some(report)
+    } else {
+        None
+    };
+
+    match result {
+        Ok(transcript) => {
+            info!(
+                "FROST coordinated signing completed in {:.2}s",
+                start.elapsed().as_secs_f64()
+            );
+            FrostSigningResult {
+                success: true,
+                signature: Some(transcript.signature),
+                output_key: Some(transcript.output_key),
+                error: None,
+                duration_secs: start.elapsed().as_secs_f64(),
+                benchmark,
+                blamed_parties,
+                transcript: Some(transcript),
+            }
+        }
+        Err(e) => {
+            error!("FROST coordinator failed: {}", e);
+            if !blamed_parties.is_empty() {
+                error!("Blamed parties: {:?}", blamed_parties);
+            }
+            FrostSigningResult {
+                success: false,
+                signature: None,
+                output_key: None,
+                error: Some(e),
+                duration_secs: start.elapsed().as_secs_f64(),
+                benchmark,
+                blamed_parties,
+                transcript: None,
+            }
+        }
+    }
+}
+
+/// Run the signer side of coordinator-based FROST signing.
+///
+/// The signer only signs if the coordinator's package matches the
+/// `message_hash` and `options` it was asked to sign, and its own
+/// commitments are included unchanged.
+pub async fn run_frost_coordinated_signer(
+    session_id: &str,
+    message_hash: &[u8; 32],
+    key_share_data: &[u8],
+    options: &SigningOptions,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostSignerResult {
+    let start = std::time::Instant::now();
+
+    let result: Result<(), String> = async {
+        let key_share: FrostKeyShare = load_key_share(key_share_data, FrostCiphersuite::Bitcoin)?;
+        let party_index = key_share.i;
+        info!(
+            "FROST signer {} joining coordinated session {}",
+            party_index, session_id
+        );
+        let mut transport = RoundTransport::new(session_id, party_index, incoming_rx, outgoing_tx);
+
+        // Round 1: send commitments to the coordinator
+        let (nonces, own_commitments) = commit(&key_share);
+        transport
+            .send(Some(COORDINATOR_INDEX), 1, &own_commitments)
+            .await?;
+
+        // Round 2: receive and check the signing package
+        let mut package = transport
+            .collect::<SigningPackage>(2, &[COORDINATOR_INDEX])
+            .await?;
+        let package = package
+            .remove(&COORDINATOR_INDEX)
+            .ok_or_else(|| "No signing package from coordinator".to_string())?;
+        if package.message_hash != *message_hash {
+            return Err(format!(
+                "Coordinator asked to sign {}, expected {}",
+                hex::encode(package.message_hash),
+                hex::encode(message_hash)
+            ));
+        }
+        if package.mode != options.mode || package.taproot_tweak != options.taproot_tweak {
+            return Err("Coordinator signing options do not match".to_string());
+        }
+        let package_signers: Vec<u16> = package.commitments.iter().map(|(j, _)| *j).collect();
+        check_signers(key_share.as_ref(), &package_signers)?;
+        let own_fingerprint = serde_json::to_vec(&own_commitments).ok();
+        let included = package
+            .commitments
+            .iter()
+            .any(|(j, c)| *j == party_index && serde_json::to_vec(c).ok() == own_fingerprint);
+        if !included {
+            return Err("Own commitments missing or altered in signing package".to_string());
+        }
+
+        // Round 3: send partial signature
+        let tweak = signing_tweak(key_share.as_ref(), options.mode, options.taproot_tweak)?;
+        let partial = sign_share(
+            &key_share,
+            nonces,
+            message_hash,
+            &package.commitments,
+            tweak,
+            options.nonce_ledger.as_ref(),
+        )
+        .await?;
+        transport.send(Some(COORDINATOR_INDEX), 3, &partial).await?;
+        Ok(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => {
+            info!(
+                "Partial signature sent to coordinator in {:.2}s",
+                start.elapsed().as_secs_f64()
+            );
+            FrostSignerResult {
+                success: true,
+                error: None,
+                duration_secs: start.elapsed().as_secs_f64(),
+            }
+        }
+        Err(e) => {
+            error!("FROST coordinated signer failed: {}", e);
+            FrostSignerResult {
+                success: false,
+                error: Some(e),
+                duration_secs: start.elapsed().as_secs_f64(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_ec::Scalar;
+
+    use super::super::local;
+    use super::*;
+
+    /// Sign with `signers` (indices at keygen) through a coordinator and
+    /// return its result. The partial signature of `corrupt`, if any, is
+    /// altered on its way to the coordinator.
+    fn run_coordinated(
+        key_shares: &[Vec<u8>],
+        signers: &[u16],
+        corrupt: Option<u16>,
+    ) -> FrostSigningResult {
+        let message_hash = [0x42u8; 32];
+        let options = SigningOptions::default();
+        let key_info = export_key_info(&key_shares[0]).unwrap();
+        let coordinator = signers.len() as u16;
+        // Relay slots: signers in order, then the coordinator
+        let slot = |party: u16| {
+            signers
+                .iter()
+                .position(|&p| p == party)
+                .map_or(coordinator, |j| j as u16)
+        };
+
+        let results = local::run_parties(
+            coordinator + 1,
+            |m: &ProtocolMessage| (slot(m.sender), m.recipient.map(slot)),
+            |j, rx, tx| {
+                let (key_info, options) = (&key_info, &options);
+                async move {
+                    if j == coordinator {
+                        return Some(
+                            run_frost_coordinator(
+                                signers,
+                                "coordinated",
+                                &message_hash,
+                                key_info,
+                                options,
+                                rx,
+                                tx,
+                            )
+                            .await,
+                        );
+                    }
+                    let signer = signers[usize::from(j)];
+                    let (signer_tx, signer_rx) = async_channel::unbounded::<ProtocolMessage>();
+                    let forward = async {
+                        while let Ok(mut msg) = signer_rx.recv().await {
+                            if corrupt == Some(signer) && msg.round == 3 {
+                                let mut partial: PartialSignature =
+                                    serde_json::from_slice(&msg.payload).unwrap();
+                                partial.0 = partial.0 + Scalar::one();
+                                msg.payload = serde_json::to_vec(&partial).unwrap();
+                            }
+                            let _ = tx.send(msg).await;
+                        }
+                    };
+                    let (result, ()) = futures::join!(
+                        run_frost_coordinated_signer(
+                            "coordinated",
+                            &message_hash,
+                            &key_shares[usize::from(signer)],
+                            options,
+                            rx,
+                            signer_tx,
+                        ),
+                        forward
+                    );
+                    assert!(result.success, "signer failed: {:?}", result.error);
+                    None
+                }
+            },
+        );
+        results.into_iter().flatten().next().unwrap()
+    }
+
+    #[test]
+    fn test_corrupted_share_blames_exactly_its_signer() {
+        let key_shares = local::key_shares(2, 3);
+        let signers = [0, 2];
+
+        let result = run_coordinated(&key_shares, &signers, Some(2));
+        assert!(!result.success);
+        assert_eq!(result.blamed_parties, vec![2]);
+
+        // Retry without the blamed signer
+        let signers = substitute_signers(&signers, &result.blamed_parties, &[0, 1, 2]).unwrap();
+        assert_eq!(signers, vec![0, 1]);
+        let result = run_coordinated(&key_shares, &signers, None);
+        assert!(result.success, "retry failed: {:?}", result.error);
+        assert!(result.blamed_parties.is_empty());
+    }
+
+    #[test]
+    fn test_substitute_signers() {
+        let signers = [0, 1, 2];
+        assert_eq!(
+            substitute_signers(&signers, &[1], &[0, 1, 2, 3, 4]),
+            Some(vec![0, 2, 3])
+        );
+        assert_eq!(substitute_signers(&signers, &[1, 2], &[0, 1, 2, 3]), None);
+    }
+}
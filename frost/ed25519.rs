@@ -0,0 +1,168 @@
+//! Ed25519 FROST, matching the hardware accelerator.
+//!
+//! The Verilog and Bluespec DKG in this repository work over Ed25519, while
+//! the Taproot flows use secp256k1. This module runs keygen and signing under
+//! Givre's Ed25519 ciphersuite so software and hardware timings compare the
+//! same curve, verifies the resulting RFC 8032 signatures, and exports DKG
+//! outputs as reference vectors for the hardware testbenches.
+
+use async_channel::{Receiver, Sender};
+use generic_ec::curves::Ed25519 as Ed25519Curve;
+use generic_ec::{Point, Scalar};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use super::ciphersuite::{
+    load_key_share, run_frost_signing_with_ciphersuite, FrostCiphersuite, FrostSuiteSigningResult,
+};
+use super::keygen::{
+    run_frost_keygen_with_ciphersuite, FrostKeygenResult, ProtocolMessage as KeygenMessage,
+};
+use super::signing::ProtocolMessage;
+
+/// Ed25519 FROST key share.
+pub type Ed25519KeyShare = givre::KeyShare<Ed25519Curve>;
+
+/// Run FROST distributed key generation over Ed25519.
+pub async fn run_frost_ed25519_keygen(
+    party_index: u16,
+    num_parties: u16,
+    threshold: u16,
+    session_id: &str,
+    incoming_rx: Receiver<KeygenMessage>,
+    outgoing_tx: Sender<KeygenMessage>,
+) -> FrostKeygenResult {
+    run_frost_keygen_with_ciphersuite(
+        party_index,
+        num_parties,
+        threshold,
+        session_id,
+        FrostCiphersuite::Ed25519,
+        incoming_rx,
+        outgoing_tx,
+    )
+    .await
+}
+
+/// Run FROST threshold signing of `message` over Ed25519.
+///
+/// On success the signature is additionally checked as a plain RFC 8032
+/// signature under the group public key.
+pub async fn run_frost_ed25519_signing(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    message: &[u8],
+    key_share_data: &[u8],
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostSuiteSigningResult {
+    let mut result = run_frost_signing_with_ciphersuite(
+        party_index,
+        parties_at_keygen,
+        session_id,
+        message,
+        key_share_data,
+        FrostCiphersuite::Ed25519,
+        incoming_rx,
+        outgoing_tx,
+    )
+    .await;
+
+    if let (Some(signature), Some(public_key)) = (&result.signature, &result.public_key) {
+        if let Err(e) = verify_ed25519_signature(public_key, message, signature) {
+            result.success = false;
+            result.signature = None;
+            result.error = Some(format!("Aggregated signature is invalid: {}", e));
+        }
+    }
+    result
+}
+
+/// Verify an RFC 8032 Ed25519 signature: `[s]B == R + [H(R || A || M)]A`.
+pub fn verify_ed25519_signature(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), String> {
+    if signature.len() != 64 {
+        return Err(format!(
+            "Expected 64-byte signature, got {}",
+            signature.len()
+        ));
+    }
+    let a = Point::<Ed25519Curve>::from_bytes(public_key)
+        .map_err(|_| "Invalid Ed25519 public key".to_string())?;
+    let r = Point::<Ed25519Curve>::from_bytes(&signature[..32])
+        .map_err(|_| "Signature R is not a valid point".to_string())?;
+    let s = Scalar::<Ed25519Curve>::from_le_bytes(&signature[32..])
+        .map_err(|_| "Signature s is not a canonical scalar".to_string())?;
+
+    let k = Scalar::<Ed25519Curve>::from_le_bytes_mod_order(
+        Sha512::new()
+            .chain_update(&signature[..32])
+            .chain_update(public_key)
+            .chain_update(message)
+            .finalize(),
+    );
+
+    if Point::generator() * s == r + a * k {
+        Ok(())
+    } else {
+        Err("Signature does not verify".to_string())
+    }
+}
+
+/// Public DKG output of one party.
+///
+/// Points are 32-byte compressed Edwards encodings (hex). The hardware
+/// testbenches do not read these yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ed25519DkgVector {
+    pub party_index: u16,
+    pub num_parties: u16,
+    pub threshold: u16,
+    /// Group public key
+    pub shared_public_key: String,
+    /// Public share of every party, by index at keygen
+    pub public_shares: Vec<String>,
+}
+
+/// Export the public DKG output of an Ed25519 key share as a reference vector.
+pub fn export_ed25519_dkg_vector(key_share_data: &[u8]) -> Result<Ed25519DkgVector, String> {
+    let key_share: Ed25519KeyShare = load_key_share(key_share_data, FrostCiphersuite::Ed25519)?;
+    let public_shares: Vec<String> = key_share
+        .public_shares
+        .iter()
+        .map(|p| hex::encode(p.to_bytes(true)))
+        .collect();
+    Ok(Ed25519DkgVector {
+        party_index: key_share.i,
+        num_parties: public_shares.len() as u16,
+        threshold: key_share.min_signers(),
+        shared_public_key: hex::encode(key_share.shared_public_key.to_bytes(true)),
+        public_shares,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_ed25519_signature_rfc8032_vector() {
+        // RFC 8032 section 7.1, test 1 (empty message)
+        let public_key =
+            hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+                .unwrap();
+        let mut signature = hex::decode(
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        )
+        .unwrap();
+        assert!(verify_ed25519_signature(&public_key, b"", &signature).is_ok());
+        assert!(verify_ed25519_signature(&public_key, b"x", &signature).is_err());
+
+        signature[63] ^= 0x01;
+        assert!(verify_ed25519_signature(&public_key, b"", &signature).is_err());
+    }
+}
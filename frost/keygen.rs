@@ -2,27 +2,103 @@
 //!
 //! This module implements threshold key generation for Schnorr signatures
 //! using the FROST protocol. The resulting keys can be used for Taproot
-//! Bitcoin transactions.
+//! Bitcoin transactions, or under another Givre ciphersuite.
 
 use async_channel::{Receiver, Sender};
 use pin_project_lite::pin_project;
 use rand::rngs::OsRng;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tracing::{error, info};
 
-use givre::ciphersuite::{Bitcoin, Ciphersuite};
-use givre::keygen::security_level::SecurityLevel128;
+use generic_ec::SecretScalar;
+use givre::ciphersuite::{Bitcoin, Ciphersuite, Ed25519, Ristretto255, Secp256k1};
+use givre::keygen::progress::PerfProfiler;
+use givre::keygen::security_level::{SecurityLevel, SecurityLevel128};
+use givre::keygen::{KeygenAborted, KeygenError};
+use sha2::Digest;
+
+use super::ciphersuite::{wrap_key_share, FrostCiphersuite};
+use super::op_count::{self, Counting, CountingDigest};
+use super::timing::{TransportLog, TransportTimings};
+use super::transcript::{KeygenTranscript, SignedKeygenTranscript, TranscriptEntry, TranscriptLog};
+use crate::bench::{BenchmarkRecorder, BenchmarkReport};
 
 /// Type alias for the FROST keygen message type
 /// The Msg type takes: Curve, SecurityLevel, Digest
-/// Using Bitcoin ciphersuite for BIP-340 compliant signatures
-type FrostKeygenMsg = givre::keygen::msg::threshold::Msg<
-    <Bitcoin as Ciphersuite>::Curve,
-    SecurityLevel128,
-    sha2::Sha256,
->;
+pub(crate) type FrostKeygenMsg<E, L, D> = givre::keygen::msg::threshold::Msg<E, L, D>;
+
+givre::keygen::define_security_level!(SecurityLevel256 { kappa_bits: 256 });
+
+/// Security parameter of the keygen proofs and commitments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum KeygenSecurityLevel {
+    /// 128-bit security (Givre default)
+    #[default]
+    Bits128,
+    /// 256-bit security
+    Bits256,
+}
+
+impl KeygenSecurityLevel {
+    pub fn bits(&self) -> u32 {
+        match self {
+            KeygenSecurityLevel::Bits128 => 128,
+            KeygenSecurityLevel::Bits256 => 256,
+        }
+    }
+}
+
+/// Hash function used for keygen commitments and proof transcripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum KeygenDigest {
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+/// Options controlling a FROST keygen session.
+///
+/// Every party must use the same options.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct KeygenOptions {
+    /// Ciphersuite the key is generated for
+    pub ciphersuite: FrostCiphersuite,
+    /// Security parameter of the protocol
+    pub security_level: KeygenSecurityLevel,
+    /// Transcript hash function
+    pub digest: KeygenDigest,
+    /// Add an echo round in which parties compare hashes of the round 1
+    /// broadcasts they received, and abort on equivocation. Needed when the
+    /// relay does not guarantee that every party sees the same broadcast.
+    pub reliable_broadcast: bool,
+}
+
+impl KeygenOptions {
+    /// Execution ID binding `session_id` to these options.
+    ///
+    /// Default options keep the plain session ID, so existing Bitcoin
+    /// deployments interoperate.
+    pub(crate) fn execution_id(&self, session_id: &str) -> Vec<u8> {
+        if *self == Self::default() {
+            return session_id.as_bytes().to_vec();
+        }
+        format!(
+            "{}/{}/{}/{:?}{}",
+            session_id,
+            self.ciphersuite,
+            self.security_level.bits(),
+            self.digest,
+            if self.reliable_broadcast { "/echo" } else { "" }
+        )
+        .into_bytes()
+    }
+}
 
 /// Protocol message for FROST keygen relay.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,26 +115,37 @@ pub struct ProtocolMessage {
 #[derive(Debug)]
 pub struct FrostKeygenResult {
     pub success: bool,
+    /// Ciphersuite the key share belongs to
+    pub ciphersuite: FrostCiphersuite,
+    /// Options the key was generated with, also recorded in the key share
+    pub options: KeygenOptions,
     /// Serialized key share (for storage)
     pub key_share_data: Option<Vec<u8>>,
     /// The aggregated public key (x-only, 32 bytes) for address derivation
     pub public_key: Option<Vec<u8>>,
+    /// Signed record of the session for offline audit
+    pub transcript: Option<SignedKeygenTranscript>,
     /// Error message if failed
     pub error: Option<String>,
     /// Duration of the protocol
     pub duration_secs: f64,
+    /// Benchmark report (if benchmarking enabled)
+    pub benchmark: Option<BenchmarkReport>,
 }
 
 pin_project! {
     /// Wrapper to adapt our async channels to round_based Stream.
-    pub struct ChannelStream {
+    pub struct ChannelStream<M> {
         #[pin]
         receiver: Receiver<ProtocolMessage>,
+        transcript: TranscriptLog,
+        timings: Option<TransportLog>,
+        _msg: PhantomData<M>,
     }
 }
 
-impl futures::Stream for ChannelStream {
-    type Item = Result<round_based::Incoming<FrostKeygenMsg>, std::io::Error>;
+impl<M: DeserializeOwned> futures::Stream for ChannelStream<M> {
+    type Item = Result<round_based::Incoming<M>, std::io::Error>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.project();
@@ -68,6 +155,18 @@ impl futures::Stream for ChannelStream {
                 // Deserialize the payload
                 match serde_json::from_slice(&msg.payload) {
                     Ok(protocol_msg) => {
+                        if let Some(Ok(mut timings)) = this.timings.as_ref().map(|t| t.lock()) {
+                            // round_based numbers rounds from 0
+                            timings.received(msg.round + 1, msg.sender);
+                        }
+                        if msg.recipient.is_none() {
+                            if let Ok(mut transcript) = this.transcript.lock() {
+                                transcript.push(TranscriptEntry {
+                                    sender: msg.sender,
+                                    payload: msg.payload.clone(),
+                                });
+                            }
+                        }
                         let incoming = round_based::Incoming {
                             id: msg.seq,
                             sender: msg.sender,
@@ -102,31 +201,41 @@ pin_project! {
         session_id: String,
         party_index: u16,
         seq: u64,
+        transcript: TranscriptLog,
+        timings: Option<TransportLog>,
     }
 }
 
-impl futures::Sink<round_based::Outgoing<FrostKeygenMsg>> for ChannelSink {
+impl<M: Serialize + round_based::ProtocolMessage> futures::Sink<round_based::Outgoing<M>>
+    for ChannelSink
+{
     type Error = std::io::Error;
 
     fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         Poll::Ready(Ok(()))
     }
 
-    fn start_send(
-        self: Pin<&mut Self>,
-        item: round_based::Outgoing<FrostKeygenMsg>,
-    ) -> Result<(), Self::Error> {
+    fn start_send(self: Pin<&mut Self>, item: round_based::Outgoing<M>) -> Result<(), Self::Error> {
         let this = self.project();
         *this.seq += 1;
         let seq = *this.seq;
 
-        let (recipient, round) = match &item.recipient {
-            round_based::MessageDestination::AllParties => (None, 0),
-            round_based::MessageDestination::OneParty(p) => (Some(*p), 0),
+        let round = item.msg.round();
+        let recipient = match &item.recipient {
+            round_based::MessageDestination::AllParties => None,
+            round_based::MessageDestination::OneParty(p) => Some(*p),
         };
 
         let payload = serde_json::to_vec(&item.msg)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if recipient.is_none() {
+            if let Ok(mut transcript) = this.transcript.lock() {
+                transcript.push(TranscriptEntry {
+                    sender: *this.party_index,
+                    payload: payload.clone(),
+                });
+            }
+        }
 
         let msg = ProtocolMessage {
             session_id: this.session_id.clone(),
@@ -141,6 +250,10 @@ impl futures::Sink<round_based::Outgoing<FrostKeygenMsg>> for ChannelSink {
         this.sender
             .try_send(msg)
             .map_err(|e| std::io::Error::other(e.to_string()))?;
+        if let Some(Ok(mut timings)) = this.timings.as_ref().map(|t| t.lock()) {
+            timings.sent(round + 1);
+        }
+        op_count::mark_round(round + 1);
 
         Ok(())
     }
@@ -166,6 +279,100 @@ pub async fn run_frost_keygen(
     incoming_rx: Receiver<ProtocolMessage>,
     outgoing_tx: Sender<ProtocolMessage>,
 ) -> FrostKeygenResult {
+    run_frost_keygen_with_ciphersuite(
+        party_index,
+        num_parties,
+        threshold,
+        session_id,
+        FrostCiphersuite::Bitcoin,
+        incoming_rx,
+        outgoing_tx,
+    )
+    .await
+}
+
+/// Run FROST distributed key generation under `ciphersuite`.
+///
+/// The serialized key share records the ciphersuite and can only be used
+/// for signing under the same suite.
+pub async fn run_frost_keygen_with_ciphersuite(
+    party_index: u16,
+    num_parties: u16,
+    threshold: u16,
+    session_id: &str,
+    ciphersuite: FrostCiphersuite,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostKeygenResult {
+    let options = KeygenOptions {
+        ciphersuite,
+        ..Default::default()
+    };
+    run_frost_keygen_with_options(
+        party_index,
+        num_parties,
+        threshold,
+        session_id,
+        &options,
+        incoming_rx,
+        outgoing_tx,
+    )
+    .await
+}
+
+/// Run FROST distributed key generation with explicit keygen options.
+///
+/// The options are bound into the execution ID, so parties configured
+/// differently cannot complete keygen together.
+pub async fn run_frost_keygen_with_options(
+    party_index: u16,
+    num_parties: u16,
+    threshold: u16,
+    session_id: &str,
+    options: &KeygenOptions,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostKeygenResult {
+    run_frost_keygen_with_benchmark(
+        party_index,
+        num_parties,
+        threshold,
+        session_id,
+        options,
+        incoming_rx,
+        outgoing_tx,
+        false,
+    )
+    .await
+}
+
+/// Run FROST distributed key generation with optional benchmarking.
+///
+/// With benchmarking enabled, setup, every protocol round (split into its
+/// commitment and proof stages, local compute and network wait per peer)
+/// and serialization are recorded as steps of the returned report.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_frost_keygen_with_benchmark(
+    party_index: u16,
+    num_parties: u16,
+    threshold: u16,
+    session_id: &str,
+    options: &KeygenOptions,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+    enable_benchmark: bool,
+) -> FrostKeygenResult {
+    let ciphersuite = options.ciphersuite;
+
+    // Initialize benchmark recorder
+    let recorder = enable_benchmark.then(|| {
+        Arc::new(Mutex::new(BenchmarkRecorder::new(
+            "FROST-Keygen",
+            party_index,
+            session_id,
+        )))
+    });
+
     info!("========================================");
     info!("  FROST KEY GENERATION STARTING");
     info!("========================================");
@@ -173,121 +380,337 @@ pub async fn run_frost_keygen(
     info!("Number of parties: {}", num_parties);
     info!("Threshold: {}-of-{}", threshold, num_parties);
     info!("Session ID: {}", session_id);
+    info!("Ciphersuite: {}", ciphersuite);
+    info!(
+        "Security level: {} bits, transcript hash: {:?}",
+        options.security_level.bits(),
+        options.digest
+    );
+    info!(
+        "Reliable broadcast: {}",
+        if options.reliable_broadcast {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    info!(
+        "Benchmarking: {}",
+        if enable_benchmark {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
 
     let start = std::time::Instant::now();
 
-    // Create execution ID from session
-    let eid = givre::keygen::ExecutionId::new(session_id.as_bytes());
-
-    // Create Stream and Sink adapters
-    let incoming_stream = ChannelStream {
-        receiver: incoming_rx,
-    };
-
-    let outgoing_sink = ChannelSink {
-        sender: outgoing_tx,
-        session_id: session_id.to_string(),
+    let session = KeygenSession {
         party_index,
-        seq: 0,
+        num_parties,
+        threshold,
+        session_id,
+        options,
+        incoming_rx,
+        outgoing_tx,
+        recorder: recorder.clone(),
+    };
+    let keygen_result = match ciphersuite {
+        FrostCiphersuite::Bitcoin => keygen_with_level::<Bitcoin>(session).await,
+        FrostCiphersuite::Secp256k1 => keygen_with_level::<Secp256k1>(session).await,
+        FrostCiphersuite::Ed25519 => keygen_with_level::<Ed25519>(session).await,
+        FrostCiphersuite::Ristretto255 => keygen_with_level::<Ristretto255>(session).await,
     };
-
-    // Box the stream and sink for the MpcParty
-    let incoming_boxed = Box::pin(incoming_stream);
-    let outgoing_boxed = Box::pin(outgoing_sink);
-
-    // Create the MPC party
-    let party = round_based::MpcParty::connected((incoming_boxed, outgoing_boxed));
-
-    // Run FROST keygen using Givre with Bitcoin ciphersuite for BIP-340 compliance
-    info!("Starting FROST keygen protocol (Bitcoin/BIP-340 ciphersuite)...");
-    let keygen_result =
-        givre::keygen::<<Bitcoin as Ciphersuite>::Curve>(eid, party_index, num_parties)
-            .set_threshold(threshold)
-            .start(&mut OsRng, party)
-            .await;
 
     let elapsed = start.elapsed();
 
+    // Complete benchmark and generate report, even on failure
+    let benchmark_report = recorder.and_then(|recorder| {
+        let mut rec = recorder.lock().ok()?;
+        rec.complete();
+        let report = rec.report();
+        report.log();
+        Some(report)
+    });
+
     match keygen_result {
-        Ok(key_share) => {
+        Ok((key_share_data, public_key_bytes, transcript)) => {
             info!(
                 "FROST keygen completed successfully in {:.2}s",
                 elapsed.as_secs_f64()
             );
-
-            // Get the public key from the key share
-            // For FROST/Schnorr, we need the x-only public key (32 bytes)
-            let shared_public_key = key_share.shared_public_key();
-
-            // Convert to bytes - get the x-coordinate for Taproot
-            // The Point type should have a method to get the coordinates
-            let pk_bytes = shared_public_key.to_bytes(true); // compressed format
-
-            // For compressed format (33 bytes): prefix || x-coordinate
-            // We need just the x-coordinate (32 bytes) for Taproot
-            let public_key_bytes = if pk_bytes.len() == 33 {
-                // Compressed: 0x02/0x03 || x (32 bytes)
-                pk_bytes[1..33].to_vec()
-            } else if pk_bytes.len() == 32 {
-                // Already x-only
-                pk_bytes.to_vec()
-            } else {
-                error!("Unexpected public key length: {}", pk_bytes.len());
-                return FrostKeygenResult {
-                    success: false,
-                    key_share_data: None,
-                    public_key: None,
-                    error: Some(format!("Unexpected public key length: {}", pk_bytes.len())),
-                    duration_secs: elapsed.as_secs_f64(),
-                };
-            };
-
-            info!(
-                "Shared public key (x-only): {}",
-                hex::encode(&public_key_bytes)
-            );
-
-            // Serialize the key share for storage
-            let key_share_data = match serde_json::to_vec(&key_share) {
-                Ok(data) => {
-                    info!("Key share serialized: {} bytes", data.len());
-                    data
-                }
-                Err(e) => {
-                    error!("Failed to serialize key share: {}", e);
-                    return FrostKeygenResult {
-                        success: false,
-                        key_share_data: None,
-                        public_key: None,
-                        error: Some(format!("Serialization error: {}", e)),
-                        duration_secs: elapsed.as_secs_f64(),
-                    };
-                }
-            };
+            info!("Shared public key: {}", hex::encode(&public_key_bytes));
+            info!("Key share serialized: {} bytes", key_share_data.len());
 
             FrostKeygenResult {
                 success: true,
+                ciphersuite,
+                options: options.clone(),
                 key_share_data: Some(key_share_data),
                 public_key: Some(public_key_bytes),
+                transcript: Some(transcript),
                 error: None,
                 duration_secs: elapsed.as_secs_f64(),
+                benchmark: benchmark_report,
             }
         }
         Err(e) => {
-            error!("FROST keygen failed: {:?}", e);
+            error!("FROST keygen failed: {}", e);
             FrostKeygenResult {
                 success: false,
+                ciphersuite,
+                options: options.clone(),
                 key_share_data: None,
                 public_key: None,
-                error: Some(format!("Protocol error: {:?}", e)),
+                transcript: None,
+                error: Some(e),
                 duration_secs: elapsed.as_secs_f64(),
+                benchmark: benchmark_report,
+            }
+        }
+    }
+}
+
+/// Turn a Givre keygen error into a message, calling out equivocation.
+fn describe_keygen_error(error: &KeygenError) -> String {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(e) = source {
+        if let Some(KeygenAborted::Round1NotReliable(mismatches)) = e.downcast_ref() {
+            let mut parties: Vec<u16> = mismatches.iter().map(|(party, _)| *party).collect();
+            parties.sort_unstable();
+            parties.dedup();
+            return format!(
+                "Broadcast equivocation: parties received different round 1 commitments ({:?})",
+                parties
+            );
+        }
+        source = e.source();
+    }
+    format!("Protocol error: {:?}", error)
+}
+
+/// Tagged key share, group public key and signed transcript.
+type KeygenOutput = (Vec<u8>, Vec<u8>, SignedKeygenTranscript);
+
+/// Inputs of one keygen run, passed down the ciphersuite/level/digest dispatch.
+struct KeygenSession<'a> {
+    party_index: u16,
+    num_parties: u16,
+    threshold: u16,
+    session_id: &'a str,
+    options: &'a KeygenOptions,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+    recorder: Option<Arc<Mutex<BenchmarkRecorder>>>,
+}
+
+async fn keygen_with_level<C: Ciphersuite>(
+    session: KeygenSession<'_>,
+) -> Result<KeygenOutput, String> {
+    match session.options.security_level {
+        KeygenSecurityLevel::Bits128 => keygen_with_digest::<C, SecurityLevel128>(session).await,
+        KeygenSecurityLevel::Bits256 => keygen_with_digest::<C, SecurityLevel256>(session).await,
+    }
+}
+
+async fn keygen_with_digest<C: Ciphersuite, L: SecurityLevel>(
+    session: KeygenSession<'_>,
+) -> Result<KeygenOutput, String> {
+    // Operation profiles count every transcript hash and curve operation
+    match (session.options.digest, op_count::is_counting()) {
+        (KeygenDigest::Sha256, false) => keygen::<C, L, sha2::Sha256>(session).await,
+        (KeygenDigest::Sha512, false) => keygen::<C, L, sha2::Sha512>(session).await,
+        (KeygenDigest::Sha256, true) => {
+            keygen::<Counting<C>, L, CountingDigest<sha2::Sha256>>(session).await
+        }
+        (KeygenDigest::Sha512, true) => {
+            keygen::<Counting<C>, L, CountingDigest<sha2::Sha512>>(session).await
+        }
+    }
+}
+
+/// Run keygen on the curve of ciphersuite `C` with security level `L` and
+/// transcript hash `D`.
+///
+/// Returns the tagged key share, the group public key (x-only for the
+/// Bitcoin suite, the curve's compressed encoding otherwise) and the signed
+/// transcript.
+async fn keygen<C: Ciphersuite, L: SecurityLevel, D: Digest + Clone + 'static>(
+    session: KeygenSession<'_>,
+) -> Result<KeygenOutput, String> {
+    let ciphersuite = session.options.ciphersuite;
+    let start = std::time::Instant::now();
+    let started_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let transcript_log = TranscriptLog::default();
+    let recorder = session.recorder;
+    let record = |step: &str, elapsed: Duration| {
+        if let Some(rec) = &recorder {
+            if let Ok(mut rec) = rec.lock() {
+                rec.record_step(step, elapsed);
             }
         }
+    };
+
+    // Step 1: Create execution ID, Stream and Sink adapters
+    let step_start = std::time::Instant::now();
+    let eid_bytes = session.options.execution_id(session.session_id);
+    let eid = givre::keygen::ExecutionId::new(&eid_bytes);
+    let transport_log = recorder.as_ref().map(|_| TransportLog::default());
+
+    let incoming_stream: ChannelStream<FrostKeygenMsg<C::Curve, L, D>> = ChannelStream {
+        receiver: session.incoming_rx,
+        transcript: transcript_log.clone(),
+        timings: transport_log.clone(),
+        _msg: PhantomData,
+    };
+
+    let outgoing_sink = ChannelSink {
+        sender: session.outgoing_tx,
+        session_id: session.session_id.to_string(),
+        party_index: session.party_index,
+        seq: 0,
+        transcript: transcript_log.clone(),
+        timings: transport_log.clone(),
+    };
+
+    // Box the stream and sink for the MpcParty
+    let incoming_boxed = Box::pin(incoming_stream);
+    let outgoing_boxed = Box::pin(outgoing_sink);
+
+    // Create the MPC party
+    let party = round_based::MpcParty::connected((incoming_boxed, outgoing_boxed));
+    record("1. Protocol setup (channels, party)", step_start.elapsed());
+
+    // Step 2-3: Run the keygen protocol, profiled per round and stage
+    info!(
+        "Starting FROST keygen protocol ({} ciphersuite)...",
+        ciphersuite
+    );
+    let mut profiler = PerfProfiler::new();
+    let builder = givre::keygen::<C::Curve>(eid, session.party_index, session.num_parties)
+        .set_threshold(session.threshold)
+        .set_security_level::<L>()
+        .set_digest::<D>()
+        .enforce_reliable_broadcast(session.options.reliable_broadcast);
+    let builder = if recorder.is_some() {
+        builder.set_progress_tracer(&mut profiler)
+    } else {
+        builder
+    };
+    if let Some(Ok(mut timings)) = transport_log.as_ref().map(|t| t.lock()) {
+        *timings = TransportTimings::new();
+    }
+    let keygen_result = builder.start(&mut OsRng, party).await;
+    let protocol_end = std::time::Instant::now();
+    if recorder.is_some() {
+        record_keygen_profile(&profiler, record);
+    }
+    if let Some(Ok(timings)) = transport_log.as_ref().map(|t| t.lock()) {
+        timings.record(3, protocol_end, record);
+    }
+    let key_share = keygen_result.map_err(|e| describe_keygen_error(&e))?;
+
+    // Step 4: Extract the group public key
+    let step_start = std::time::Instant::now();
+    let pk_bytes = key_share.shared_public_key().to_bytes(true);
+    let public_key_bytes = if ciphersuite == FrostCiphersuite::Bitcoin {
+        // Compressed: 0x02/0x03 || x (32 bytes); Taproot needs just x
+        if pk_bytes.len() != 33 {
+            return Err(format!("Unexpected public key length: {}", pk_bytes.len()));
+        }
+        pk_bytes[1..33].to_vec()
+    } else {
+        pk_bytes.to_vec()
+    };
+    record("4. Extract public key", step_start.elapsed());
+
+    // Step 5: Serialize the key share
+    let step_start = std::time::Instant::now();
+    let key_share_data = wrap_key_share(session.options, &key_share)
+        .map_err(|e| format!("Serialization error: {}", e))?;
+    record("5. Serialize key_share", step_start.elapsed());
+
+    // Step 6: Record and sign the transcript
+    let step_start = std::time::Instant::now();
+    let mut broadcasts = transcript_log
+        .lock()
+        .map(|log| log.clone())
+        .map_err(|_| "Keygen transcript lock poisoned".to_string())?;
+    broadcasts.sort();
+    let key_info: &givre::key_share::KeyInfo<C::Curve> = key_share.as_ref();
+    let transcript = KeygenTranscript {
+        session_id: session.session_id.to_string(),
+        options: session.options.clone(),
+        party_index: session.party_index,
+        num_parties: session.num_parties,
+        threshold: session.threshold,
+        broadcasts,
+        started_at,
+        duration_secs: start.elapsed().as_secs_f64(),
+        shared_public_key: pk_bytes.to_vec(),
+        public_shares: key_info
+            .public_shares
+            .iter()
+            .map(|p| p.to_bytes(true).to_vec())
+            .collect(),
+        share_preimages: (0..session.num_parties)
+            .map(|j| {
+                key_info
+                    .share_preimage(j)
+                    .map(|x| x.to_be_bytes().to_vec())
+                    .ok_or_else(|| format!("No share preimage for party {}", j))
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    let secret_share: &SecretScalar<C::Curve> = key_share.x.as_ref();
+    let transcript = transcript.sign(secret_share.as_ref())?;
+    record("6. Sign transcript", step_start.elapsed());
+
+    Ok((key_share_data, public_key_bytes, transcript))
+}
+
+/// Record Givre's keygen profile as benchmark steps.
+///
+/// The computation of each round is split into its named stages
+/// (commitments, proof generation and verification) and the rest. Time spent
+/// waiting on peers comes from the transport timings instead.
+fn record_keygen_profile(profiler: &PerfProfiler, record: impl Fn(&str, Duration)) {
+    let profile = match profiler.get_report() {
+        Ok(profile) => profile,
+        Err(e) => {
+            error!("Failed to read keygen profile: {:?}", e);
+            return;
+        }
+    };
+    record("2. Keygen setup", profile.setup);
+    for (i, round) in profile.rounds.iter().enumerate() {
+        let round_number = i + 1;
+        let mut staged = Duration::ZERO;
+        for stage in &round.stages {
+            record(
+                &format!("3.{} Round {}: {}", round_number, round_number, stage.name),
+                stage.duration,
+            );
+            staged += stage.duration;
+        }
+        record(
+            &format!(
+                "3.{} Round {}: other computation",
+                round_number, round_number
+            ),
+            round.computation.saturating_sub(staged),
+        );
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::ciphersuite::key_share_options;
+    use super::super::local;
+    use super::super::signing::SigningOptions;
     use super::*;
 
     #[test]
@@ -307,4 +730,128 @@ mod tests {
         assert_eq!(msg.session_id, deserialized.session_id);
         assert_eq!(msg.sender, deserialized.sender);
     }
+
+    #[test]
+    fn test_keygen_options_bound_into_execution_id() {
+        let default = KeygenOptions::default();
+        assert_eq!(default.execution_id("s"), b"s".to_vec());
+
+        let strict = KeygenOptions {
+            security_level: KeygenSecurityLevel::Bits256,
+            digest: KeygenDigest::Sha512,
+            ..Default::default()
+        };
+        assert_ne!(strict.execution_id("s"), default.execution_id("s"));
+
+        let echo = KeygenOptions {
+            reliable_broadcast: true,
+            ..Default::default()
+        };
+        assert_ne!(echo.execution_id("s"), default.execution_id("s"));
+    }
+
+    /// Change the first hex string or byte array in `value`.
+    fn tamper(value: &mut serde_json::Value) -> bool {
+        match value {
+            serde_json::Value::String(s) if s.len() >= 2 && hex::decode(&*s).is_ok() => {
+                let flipped = if s.starts_with('0') { '1' } else { '0' };
+                s.replace_range(..1, &flipped.to_string());
+                true
+            }
+            serde_json::Value::Array(items) if items.first().is_some_and(|v| v.is_u64()) => {
+                items[0] = (items[0].as_u64().unwrap() ^ 1).into();
+                true
+            }
+            serde_json::Value::Array(items) => items.iter_mut().any(tamper),
+            serde_json::Value::Object(fields) => fields.values_mut().any(tamper),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_round1_equivocation_aborts_keygen() {
+        let options = KeygenOptions {
+            reliable_broadcast: true,
+            ..Default::default()
+        };
+        let results = local::run_parties(
+            3,
+            |m: &ProtocolMessage| (m.sender, m.recipient),
+            |i, rx, tx| {
+                let options = &options;
+                async move {
+                    // Party 2 sees a different round 1 broadcast from party 0
+                    // than everyone else
+                    let (inbox_tx, inbox_rx) = async_channel::unbounded::<ProtocolMessage>();
+                    let forward = async move {
+                        while let Ok(mut msg) = rx.recv().await {
+                            if i == 2 && msg.sender == 0 && msg.round == 0 {
+                                let mut payload: serde_json::Value =
+                                    serde_json::from_slice(&msg.payload).unwrap();
+                                assert!(tamper(&mut payload));
+                                msg.payload = serde_json::to_vec(&payload).unwrap();
+                            }
+                            if inbox_tx.send(msg).await.is_err() {
+                                break;
+                            }
+                        }
+                    };
+                    let keygen = run_frost_keygen_with_options(
+                        i,
+                        3,
+                        2,
+                        "equivocation",
+                        options,
+                        inbox_rx,
+                        tx,
+                    );
+                    // Stop forwarding once keygen has finished
+                    match futures::future::select(Box::pin(keygen), Box::pin(forward)).await {
+                        futures::future::Either::Left((result, _)) => result,
+                        futures::future::Either::Right(_) => panic!("relay closed during keygen"),
+                    }
+                }
+            },
+        );
+
+        for result in results {
+            assert!(!result.success);
+            let error = result.error.unwrap();
+            assert!(error.starts_with("Broadcast equivocation"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_keygen_options_round_trip_through_key_share() {
+        let options = KeygenOptions {
+            security_level: KeygenSecurityLevel::Bits256,
+            digest: KeygenDigest::Sha512,
+            ..Default::default()
+        };
+        let results = local::run_keygen(2, 3, &options);
+        let key_shares: Vec<Vec<u8>> = results
+            .iter()
+            .map(|result| {
+                assert!(result.success, "keygen failed: {:?}", result.error);
+                assert_eq!(result.options, options);
+                result.key_share_data.clone().unwrap()
+            })
+            .collect();
+        for key_share in &key_shares {
+            assert_eq!(key_share_options(key_share), Ok(Some(options.clone())));
+        }
+
+        // The shares sign like any other Bitcoin key
+        let message_hash = [0x42u8; 32];
+        let signing = SigningOptions::default();
+        for result in local::run_signing(&key_shares, &[1, 2], &message_hash, &signing) {
+            assert!(result.success, "signing failed: {:?}", result.error);
+            let output_key = result.output_key.unwrap();
+            assert!(result
+                .signature
+                .unwrap()
+                .verify(&output_key, &message_hash)
+                .is_ok());
+        }
+    }
 }
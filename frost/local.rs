@@ -0,0 +1,156 @@
+//! In-process network of protocol parties.
+//!
+//! Connects the parties of one protocol run through an in-memory relay, for
+//! the `frost-bench` harness, the operation profiles and the end-to-end
+//! tests. Every party runs on its own thread, so its timings only include
+//! its own computation.
+
+use async_channel::{Receiver, Sender};
+
+use super::keygen::{self, run_frost_keygen_with_options, FrostKeygenResult, KeygenOptions};
+#[cfg(test)]
+use super::signing::{self, run_frost_signing_with_options, FrostSigningResult, SigningOptions};
+
+/// Forward every message to its recipient, or to every other party for
+/// broadcasts, until all parties have hung up.
+///
+/// Each delivered copy is passed through `hop`; copies it returns `None` for
+/// are dropped.
+async fn relay<M>(
+    hub: Receiver<M>,
+    inboxes: Vec<Sender<M>>,
+    route: impl Fn(&M) -> (u16, Option<u16>),
+    hop: impl Fn(&M) -> Option<M>,
+) {
+    while let Ok(msg) = hub.recv().await {
+        let (sender, recipient) = route(&msg);
+        for (j, inbox) in inboxes.iter().enumerate() {
+            let j = j as u16;
+            if recipient.map_or(j != sender, |r| r == j) {
+                if let Some(copy) = hop(&msg) {
+                    let _ = inbox.send(copy).await;
+                }
+            }
+        }
+    }
+}
+
+/// Channels of `parties` parties connected through one relay.
+#[allow(clippy::type_complexity)]
+fn network<M>(parties: u16) -> (Vec<(Receiver<M>, Sender<M>)>, Receiver<M>, Vec<Sender<M>>) {
+    let (hub_tx, hub_rx) = async_channel::unbounded();
+    let (inboxes, endpoints): (Vec<_>, Vec<_>) = (0..parties)
+        .map(|_| {
+            let (tx, rx) = async_channel::unbounded();
+            (tx, (rx, hub_tx.clone()))
+        })
+        .unzip();
+    (endpoints, hub_rx, inboxes)
+}
+
+/// Run `parties` parties to completion, each on its own thread, and return
+/// their outputs in party order.
+///
+/// `route` maps a message to its (sender, recipient) party; messages without
+/// a recipient go to every other party.
+pub(crate) fn run_parties<M, T, F, Fut>(
+    parties: u16,
+    route: impl Fn(&M) -> (u16, Option<u16>) + Send,
+    party: F,
+) -> Vec<T>
+where
+    M: Clone + Send,
+    T: Send,
+    F: Fn(u16, Receiver<M>, Sender<M>) -> Fut + Sync,
+    Fut: std::future::Future<Output = T>,
+{
+    run_parties_over(parties, route, |m: &M| Some(m.clone()), party)
+}
+
+/// Like [`run_parties`], with every delivered message passed through `hop`
+/// on the relay thread, as a network link would, e.g. to encode and decode
+/// it. Messages `hop` returns `None` for are dropped.
+pub(crate) fn run_parties_over<M, T, F, Fut>(
+    parties: u16,
+    route: impl Fn(&M) -> (u16, Option<u16>) + Send,
+    hop: impl Fn(&M) -> Option<M> + Send,
+    party: F,
+) -> Vec<T>
+where
+    M: Send,
+    T: Send,
+    F: Fn(u16, Receiver<M>, Sender<M>) -> Fut + Sync,
+    Fut: std::future::Future<Output = T>,
+{
+    let (endpoints, hub, inboxes) = network::<M>(parties);
+    std::thread::scope(|scope| {
+        scope.spawn(move || futures::executor::block_on(relay(hub, inboxes, route, hop)));
+        let handles: Vec<_> = endpoints
+            .into_iter()
+            .enumerate()
+            .map(|(i, (rx, tx))| {
+                let party = &party;
+                scope.spawn(move || futures::executor::block_on(party(i as u16, rx, tx)))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("party thread panicked"))
+            .collect()
+    })
+}
+
+/// Run `threshold`-of-`parties` keygen and return every party's result.
+pub(crate) fn run_keygen(
+    threshold: u16,
+    parties: u16,
+    options: &KeygenOptions,
+) -> Vec<FrostKeygenResult> {
+    let session_id = format!("keygen-{}-of-{}", threshold, parties);
+    run_parties(
+        parties,
+        |m: &keygen::ProtocolMessage| (m.sender, m.recipient),
+        |i, rx, tx| {
+            run_frost_keygen_with_options(i, parties, threshold, &session_id, options, rx, tx)
+        },
+    )
+}
+
+/// Run Bitcoin keygen and return the key shares of all parties.
+#[cfg(test)]
+pub(crate) fn key_shares(threshold: u16, parties: u16) -> Vec<Vec<u8>> {
+    run_keygen(threshold, parties, &Default::default())
+        .into_iter()
+        .map(|result| {
+            assert!(result.success, "keygen failed: {:?}", result.error);
+            result.key_share_data.expect("keygen returned no key share")
+        })
+        .collect()
+}
+
+/// Sign `message_hash` with the key shares of `signers` (indices at keygen)
+/// and return every signer's result.
+#[cfg(test)]
+pub(crate) fn run_signing(
+    key_shares: &[Vec<u8>],
+    signers: &[u16],
+    message_hash: &[u8; 32],
+    options: &SigningOptions,
+) -> Vec<FrostSigningResult> {
+    run_parties(
+        signers.len() as u16,
+        |m: &signing::ProtocolMessage| (m.sender, m.recipient),
+        |i, rx, tx| {
+            run_frost_signing_with_options(
+                i,
+                signers,
+                "signing",
+                message_hash,
+                &key_shares[usize::from(signers[usize::from(i)])],
+                options,
+                rx,
+                tx,
+            )
+        },
+    )
+}
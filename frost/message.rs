@@ -0,0 +1,143 @@
+//! FROST signing of arbitrary off-chain messages.
+//!
+//! This module signs attestations, Nostr events and other off-chain data
+//! with a FROST group key. The message is hashed here, optionally with a
+//! BIP-340 tagged hash, and signed with the untweaked group key. It is kept
+//! separate from the transaction-signing path so a message digest can never
+//! be mistaken for a sighash.
+
+use async_channel::{Receiver, Sender};
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use super::signing::{
+    run_frost_signing_session, FrostSigningResult, ProtocolMessage, SignedHash, SigningMode,
+    SigningOptions,
+};
+
+/// Compute the BIP-340 tagged hash `SHA256(SHA256(tag) || SHA256(tag) || msg)`.
+pub fn tagged_hash(tag: &str, msg: &[u8]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag.as_bytes());
+    Sha256::new()
+        .chain_update(tag_hash)
+        .chain_update(tag_hash)
+        .chain_update(msg)
+        .finalize()
+        .into()
+}
+
+/// Digest signed for `message`: the tagged hash if `tag` is given, plain
+/// SHA-256 otherwise (as used for Nostr event IDs).
+pub fn message_digest(message: &[u8], tag: Option<&str>) -> [u8; 32] {
+    match tag {
+        Some(tag) => tagged_hash(tag, message),
+        None => Sha256::digest(message).into(),
+    }
+}
+
+/// Run FROST signing over an arbitrary message.
+///
+/// The signature is a plain BIP-340 signature that verifies against the
+/// untweaked group key from keygen, over `message_digest(message, tag)`.
+/// This is the only way to sign an off-chain digest; the transaction-signing
+/// entry points only sign sighashes.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_frost_message_signing(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    message: &[u8],
+    tag: Option<&str>,
+    key_share_data: &[u8],
+    enable_benchmark: bool,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostSigningResult {
+    let digest = message_digest(message, tag);
+    info!(
+        "Signing {}-byte message (tag: {}), digest: {}",
+        message.len(),
+        tag.unwrap_or("none"),
+        hex::encode(digest)
+    );
+
+    let options = SigningOptions {
+        mode: SigningMode::ScriptPath,
+        enable_benchmark,
+        verify_signature: true,
+        ..Default::default()
+    };
+    run_frost_signing_session(
+        party_index,
+        parties_at_keygen,
+        session_id,
+        &digest,
+        SignedHash::MessageDigest,
+        key_share_data,
+        &options,
+        incoming_rx,
+        outgoing_tx,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ciphersuite::{load_key_share, FrostCiphersuite};
+    use super::super::local;
+    use super::super::signing::FrostKeyShare;
+    use super::*;
+
+    #[test]
+    fn test_message_digest() {
+        // TapTweak of the BIP-86 test vector internal key
+        let internal_key =
+            hex::decode("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115")
+                .unwrap();
+        assert_eq!(
+            hex::encode(message_digest(&internal_key, Some("TapTweak"))),
+            "2ca01ed85cf6b6526f73d39a1111cd80333bfdc00ce98992859848a90a6f0258"
+        );
+        assert_eq!(
+            hex::encode(message_digest(b"hello", None)),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[test]
+    fn test_message_signature_verifies_against_untweaked_key() {
+        let key_shares = local::key_shares(2, 3);
+        let signers = [0u16, 2];
+        let message = b"attestation";
+        let results = local::run_parties(
+            signers.len() as u16,
+            |m: &ProtocolMessage| (m.sender, m.recipient),
+            |i, rx, tx| {
+                let key_share = &key_shares[usize::from(signers[usize::from(i)])];
+                run_frost_message_signing(
+                    i,
+                    &signers,
+                    "message",
+                    message,
+                    Some("test/attestation"),
+                    key_share,
+                    false,
+                    rx,
+                    tx,
+                )
+            },
+        );
+
+        let digest = message_digest(message, Some("test/attestation"));
+        for result in results {
+            assert!(result.success, "signing failed: {:?}", result.error);
+            assert!(result.benchmark.is_none());
+            let output_key = result.output_key.unwrap();
+            assert!(result
+                .signature
+                .unwrap()
+                .verify(&output_key, &digest)
+                .is_ok());
+        }
+    }
+}
@@ -2,12 +2,96 @@
 //!
 //! This module implements the FROST protocol for threshold Schnorr signatures.
 //! It includes:
-//! - Distributed key generation
+//! - Distributed key generation (configurable security level and hash)
+//! - Per-step benchmarking of keygen and signing, with per-round compute
+//!   and network wait times
+//! - JSON/CSV benchmark export, aggregation across runs and comparison
+//!   against Verilog testbench cycle counts
+//! - In-process keygen/signing benchmark harness (`frost-bench`)
+//! - Per-round operation counts (hashes, multi-scalar multiplication terms)
+//!   from real keygen and signing runs
+//! - Bitcoin, secp256k1, Ed25519 and Ristretto255 ciphersuites
+//! - Ed25519 keygen and signing matching the hardware accelerator
 //! - Threshold signing (BIP-340 compatible for Taproot)
+//! - Batch signing of many messages in one session
+//! - Coordinator-based (hub-and-spoke) signing
+//! - Signer-subset selection and retry
+//! - Nonce preprocessing for one-round online signing
+//! - Persistent nonce-reuse protection
+//! - Off-chain message signing with BIP-340 tagged hashes
+//! - Signed keygen and signing transcripts, with an offline verifier
+//! - Taproot output key and address derivation
+//! - PSBT input signing
+//! - BIP-341 sighash computation
 
+pub mod batch;
+pub mod bench_export;
+pub mod bench_harness;
+pub mod ciphersuite;
+pub mod coordinator;
+pub mod ed25519;
 pub mod keygen;
+mod local;
+pub mod message;
+pub mod nonce_ledger;
+pub mod op_count;
+pub mod orchestrator;
+pub mod presign;
+pub mod psbt;
+pub mod rounds;
+pub mod sighash;
 pub mod signing;
+pub mod taproot;
+pub mod timing;
+pub mod transcript;
+pub mod verify;
 
 // Explicit re-exports to avoid ambiguity
-pub use keygen::{run_frost_keygen, FrostKeygenResult};
-pub use signing::{run_frost_signing, FrostKeyShare, FrostSigningResult, SchnorrSignature};
+pub use batch::{run_frost_batch_signing, FrostBatchSigningResult};
+pub use bench_export::{
+    compare_report_files, compare_reports, AggregateReport, ReportDiff, RunReport, StepStats,
+};
+pub use bench_harness::{
+    run_frost_bench, run_frost_bench_cli, BenchCodec, BenchConfig, BenchGridPoint,
+};
+pub use ciphersuite::{
+    key_share_ciphersuite, key_share_options, migrate_key_share,
+    run_frost_signing_with_ciphersuite, run_frost_signing_with_ciphersuite_benchmark,
+    FrostCiphersuite, FrostSuiteSigningResult,
+};
+pub use coordinator::{
+    export_key_info, run_frost_coordinated_signer, run_frost_coordinator, substitute_signers,
+    FrostSignerResult,
+};
+pub use ed25519::{
+    export_ed25519_dkg_vector, run_frost_ed25519_keygen, run_frost_ed25519_signing,
+    verify_ed25519_signature, Ed25519DkgVector,
+};
+pub use keygen::{
+    run_frost_keygen, run_frost_keygen_with_benchmark, run_frost_keygen_with_ciphersuite,
+    run_frost_keygen_with_options, FrostKeygenResult, KeygenDigest, KeygenOptions,
+    KeygenSecurityLevel,
+};
+pub use message::{message_digest, run_frost_message_signing, tagged_hash};
+pub use nonce_ledger::{NonceLedger, NonceLedgerHandle};
+pub use op_count::{profile_keygen_ops, profile_signing_ops, OpCounts, OpProfile};
+pub use orchestrator::{
+    answer_signing_probe, run_frost_orchestrated_signing, FrostOrchestratedSigningResult,
+    OrchestratorConfig,
+};
+pub use presign::{
+    run_frost_preprocessing, run_frost_presigned_signing, FrostPreprocessingResult, PresignatureId,
+    PresignatureStore,
+};
+pub use psbt::{sign_psbt_with_frost, FrostPsbtSigningResult};
+pub use sighash::{taproot_sighash, TapLeafSpend};
+pub use signing::{
+    run_frost_signing, run_frost_signing_with_options, FrostKeyShare, FrostSigningResult,
+    SchnorrSignature, SigningMode, SigningOptions,
+};
+pub use taproot::{derive_taproot_output, Network, TaprootOutput, TaprootTweak};
+pub use transcript::{
+    compare_keygen_transcripts, verify_keygen_broadcasts, verify_keygen_transcript,
+    KeygenTranscript, SignedKeygenTranscript, SigningTranscript,
+};
+pub use verify::{run_frost_verify_cli, verify_keygen, verify_signing, VerifyReport};
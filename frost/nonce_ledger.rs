@@ -0,0 +1,412 @@
+//! Persistent ledger of consumed FROST nonces.
+//!
+//! Signing twice with the same nonce leaks the key share. Once nonces can be
+//! preprocessed or sessions retried, every nonce commitment a party signs
+//! with is recorded here first, and a second use is refused. The ledger is an
+//! append-only JSON-lines file that is fsync'd before the partial signature
+//! is computed, so the guarantee holds across restarts and crashes.
+//!
+//! The round-by-round Bitcoin signing flows (batch, presigned and
+//! coordinated) consult the ledger given in their options before releasing a
+//! partial signature. Interactive and message signing run Givre's
+//! full-signing protocol, which draws fresh nonces inside the session.
+//!
+//! Signing tasks reach the ledger through a [`NonceLedgerHandle`], whose
+//! single worker thread owns the file and performs every blocking append, so
+//! no executor thread ever waits for an fsync.
+
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use async_channel::Sender;
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+/// One consumed nonce, as stored in the ledger file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LedgerEntry {
+    /// Group public key of the key share (hex)
+    key_id: String,
+    /// Signer index at keygen
+    signer: u16,
+    /// SHA-256 fingerprint of the nonce commitments (hex)
+    commitment: String,
+}
+
+/// Append-only, fsync'd record of every nonce commitment used for signing.
+#[derive(Debug)]
+pub struct NonceLedger {
+    path: PathBuf,
+    file: File,
+    consumed: HashSet<(String, u16, String)>,
+}
+
+impl NonceLedger {
+    /// Open the ledger at `path`, creating it if needed and loading all
+    /// previously consumed nonces.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let mut consumed = HashSet::new();
+
+        if path.exists() {
+            let mut contents = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read nonce ledger {}: {}", path.display(), e))?;
+
+            // Every entry is written together with its newline, so a missing
+            // final newline marks a torn write whose fsync never completed and
+            // for which no partial signature was released. Drop it.
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                let complete_len = contents.rfind('\n').map_or(0, |pos| pos + 1);
+                warn!(
+                    "Discarding incomplete nonce ledger entry ({} bytes)",
+                    contents.len() - complete_len
+                );
+                contents.truncate(complete_len);
+                OpenOptions::new()
+                    .write(true)
+                    .open(&path)
+                    .and_then(|f| {
+                        f.set_len(complete_len as u64)?;
+                        f.sync_all()
+                    })
+                    .map_err(|e| format!("Failed to repair nonce ledger: {}", e))?;
+            }
+
+            for (n, line) in contents.lines().enumerate() {
+                let entry: LedgerEntry = serde_json::from_str(line)
+                    .map_err(|e| format!("Corrupt nonce ledger entry at line {}: {}", n + 1, e))?;
+                consumed.insert((entry.key_id, entry.signer, entry.commitment));
+            }
+        }
+
+        let created = !path.exists();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open nonce ledger {}: {}", path.display(), e))?;
+        if created {
+            sync_parent_dir(&path)
+                .map_err(|e| format!("Failed to persist nonce ledger {}: {}", path.display(), e))?;
+        }
+
+        Ok(Self {
+            path,
+            file,
+            consumed,
+        })
+    }
+
+    /// Path of the ledger file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the nonce with `fingerprint` was already used by `signer` under `key_id`.
+    pub fn is_consumed(&self, key_id: &[u8], signer: u16, fingerprint: &[u8; 32]) -> bool {
+        self.consumed
+            .contains(&(hex::encode(key_id), signer, hex::encode(fingerprint)))
+    }
+
+    /// Record a nonce as consumed, refusing if it was used before.
+    ///
+    /// Returns only after the entry is durably written to disk.
+    pub fn consume(
+        &mut self,
+        key_id: &[u8],
+        signer: u16,
+        fingerprint: &[u8; 32],
+    ) -> Result<(), String> {
+        let entry = LedgerEntry {
+            key_id: hex::encode(key_id),
+            signer,
+            commitment: hex::encode(fingerprint),
+        };
+        let key = (entry.key_id.clone(), signer, entry.commitment.clone());
+        if self.consumed.contains(&key) {
+            error!(
+                "Refusing to reuse nonce {} for signer {}",
+                entry.commitment, signer
+            );
+            return Err(format!(
+                "Nonce {} was already used by signer {}",
+                entry.commitment, signer
+            ));
+        }
+
+        let mut line = serde_json::to_vec(&entry)
+            .map_err(|e| format!("Failed to serialize nonce ledger entry: {}", e))?;
+        line.push(b'\n');
+        self.file
+            .write_all(&line)
+            .and_then(|_| self.file.sync_all())
+            .map_err(|e| format!("Failed to persist nonce ledger entry: {}", e))?;
+
+        self.consumed.insert(key);
+        Ok(())
+    }
+}
+
+/// Request to the ledger worker to consume one nonce.
+struct ConsumeRequest {
+    key_id: Vec<u8>,
+    signer: u16,
+    fingerprint: [u8; 32],
+    reply: Sender<Result<(), String>>,
+}
+
+/// Handle to a [`NonceLedger`] owned by a long-lived worker thread.
+///
+/// Handles are cheap to clone. The worker serves requests in order and
+/// exits, closing the ledger, once the last handle is dropped.
+#[derive(Debug, Clone)]
+pub struct NonceLedgerHandle {
+    requests: Sender<ConsumeRequest>,
+}
+
+impl NonceLedgerHandle {
+    /// Open the ledger at `path` and start its worker.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        Self::spawn(NonceLedger::open(path)?)
+    }
+
+    /// Move `ledger` to a new worker thread.
+    pub fn spawn(mut ledger: NonceLedger) -> Result<Self, String> {
+        let (requests, incoming) = async_channel::unbounded::<ConsumeRequest>();
+        std::thread::Builder::new()
+            .name("nonce-ledger".to_string())
+            .spawn(move || {
+                while let Ok(request) = incoming.recv_blocking() {
+                    let result =
+                        ledger.consume(&request.key_id, request.signer, &request.fingerprint);
+                    let _ = request.reply.try_send(result);
+                }
+            })
+            .map_err(|e| format!("Failed to start nonce ledger worker: {}", e))?;
+        Ok(Self { requests })
+    }
+
+    /// Record a nonce as consumed, refusing if it was used before.
+    ///
+    /// Resolves only after the entry is durably written to disk.
+    pub async fn consume(
+        &self,
+        key_id: &[u8],
+        signer: u16,
+        fingerprint: &[u8; 32],
+    ) -> Result<(), String> {
+        let (reply, result) = async_channel::bounded(1);
+        self.requests
+            .send(ConsumeRequest {
+                key_id: key_id.to_vec(),
+                signer,
+                fingerprint: *fingerprint,
+                reply,
+            })
+            .await
+            .map_err(|_| "Nonce ledger worker has stopped".to_string())?;
+        result
+            .recv()
+            .await
+            .map_err(|_| "Nonce ledger worker exited without a result".to_string())?
+    }
+}
+
+/// fsync the directory holding `path`, so a newly created file survives a crash.
+#[cfg(unix)]
+pub(super) fn sync_parent_dir(path: &Path) -> std::io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+/// Other platforms cannot open a directory to sync it.
+#[cfg(not(unix))]
+pub(super) fn sync_parent_dir(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::batch::run_frost_batch_signing;
+    use super::super::local;
+    use super::super::presign::{
+        run_frost_preprocessing, run_frost_presigned_signing, PresignatureStore,
+    };
+    use super::super::signing::{ProtocolMessage, SigningOptions};
+    use super::*;
+
+    #[test]
+    fn test_nonce_ledger_refuses_reuse_across_reopen() {
+        let path = std::env::temp_dir().join(format!(
+            "frost-nonce-ledger-{}-{}.jsonl",
+            std::process::id(),
+            line!()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let key_id = [9u8; 32];
+        let fingerprint = [1u8; 32];
+
+        {
+            let mut ledger = NonceLedger::open(&path).unwrap();
+            assert!(ledger.consume(&key_id, 0, &fingerprint).is_ok());
+            assert!(ledger.consume(&key_id, 0, &fingerprint).is_err());
+            assert!(ledger.consume(&key_id, 1, &fingerprint).is_ok());
+        }
+
+        // Simulate a crash mid-write of a further entry
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"key_id\":\"09")
+            .unwrap();
+
+        let mut ledger = NonceLedger::open(&path).unwrap();
+        assert!(ledger.is_consumed(&key_id, 0, &fingerprint));
+        assert!(ledger.consume(&key_id, 0, &fingerprint).is_err());
+        assert!(ledger.consume(&key_id, 2, &fingerprint).is_ok());
+        drop(ledger);
+
+        // The torn entry was dropped, so later entries still parse
+        let ledger = NonceLedger::open(&path).unwrap();
+        assert!(ledger.is_consumed(&key_id, 2, &fingerprint));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_signing_records_nonces_in_ledger() {
+        let path = std::env::temp_dir().join(format!(
+            "frost-nonce-ledger-{}-{}.jsonl",
+            std::process::id(),
+            line!()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let options = SigningOptions {
+            nonce_ledger: Some(NonceLedgerHandle::open(&path).unwrap()),
+            ..Default::default()
+        };
+
+        let key_shares = local::key_shares(2, 3);
+        let signers = [0u16, 2];
+        let results = local::run_parties(
+            signers.len() as u16,
+            |m: &ProtocolMessage| (m.sender, m.recipient),
+            |i, rx, tx| {
+                run_frost_batch_signing(
+                    i,
+                    &signers,
+                    "ledger",
+                    &[[0x42u8; 32]],
+                    &key_shares[usize::from(signers[usize::from(i)])],
+                    &options,
+                    rx,
+                    tx,
+                )
+            },
+        );
+        for result in results {
+            assert!(result.success, "signing failed: {:?}", result.error);
+        }
+        // Every entry was fsync'd before its partial signature was released
+        assert_eq!(NonceLedger::open(&path).unwrap().consumed.len(), 2);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_restored_presignature_refused_after_restart() {
+        let key_shares = local::key_shares(2, 3);
+        let signers = [0u16, 2];
+        let key_share = |i: u16| &key_shares[usize::from(signers[usize::from(i)])];
+        let route = |m: &ProtocolMessage| (m.sender, m.recipient);
+        let path = |kind: &str, j: u16| {
+            std::env::temp_dir().join(format!(
+                "frost-restart-{}-{}.{}",
+                std::process::id(),
+                j,
+                kind
+            ))
+        };
+        for &j in &signers {
+            for kind in ["store", "backup", "ledger"] {
+                let _ = std::fs::remove_file(path(kind, j));
+            }
+        }
+
+        let preprocessed = local::run_parties(2, route, |i, rx, tx| {
+            let store = path("store", signers[usize::from(i)]);
+            async move {
+                let mut store = PresignatureStore::open(store).unwrap();
+                run_frost_preprocessing(i, &signers, "pre", key_share(i), 1, &mut store, rx, tx)
+                    .await
+            }
+        });
+        let id = preprocessed[0].presignature_ids[0];
+        // Back up the stores while they still hold the presignature
+        for &j in &signers {
+            std::fs::copy(path("store", j), path("backup", j)).unwrap();
+        }
+
+        let sign = |session_id: &'static str, message_hash: [u8; 32]| {
+            local::run_parties(2, route, move |i, rx, tx| {
+                let j = signers[usize::from(i)];
+                async move {
+                    let mut store = PresignatureStore::open(path("store", j)).unwrap();
+                    let options = SigningOptions {
+                        nonce_ledger: Some(NonceLedgerHandle::open(path("ledger", j)).unwrap()),
+                        ..Default::default()
+                    };
+                    run_frost_presigned_signing(
+                        i,
+                        &signers,
+                        session_id,
+                        id,
+                        &message_hash,
+                        key_share(i),
+                        &options,
+                        &mut store,
+                        rx,
+                        tx,
+                    )
+                    .await
+                }
+            })
+        };
+        for result in sign("sign", [1u8; 32]) {
+            assert!(result.success, "signing failed: {:?}", result.error);
+        }
+
+        // Restart with the backed-up stores: the presignature is back on disk,
+        // but the ledger refuses to sign a second message with its nonce
+        for &j in &signers {
+            std::fs::rename(path("backup", j), path("store", j)).unwrap();
+            let store = PresignatureStore::open(path("store", j)).unwrap();
+            assert_eq!(store.available_ids(), vec![id]);
+        }
+        for result in sign("sign-again", [2u8; 32]) {
+            assert!(!result.success);
+            assert!(
+                result
+                    .error
+                    .as_deref()
+                    .unwrap()
+                    .contains("was already used by signer"),
+                "unexpected error: {:?}",
+                result.error
+            );
+            assert!(result.signature.is_none());
+        }
+
+        for &j in &signers {
+            for kind in ["store", "ledger"] {
+                std::fs::remove_file(path(kind, j)).unwrap();
+            }
+        }
+    }
+}
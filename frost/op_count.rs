@@ -0,0 +1,903 @@
+//! Operation counts of FROST keygen and signing per protocol round.
+//!
+//! Sizes the hardware units (`ed25519_scalar_mult`, `sha256_core`) from real
+//! protocol runs rather than from estimates. The profiles run keygen and
+//! signing in-process for a given (t, n) on [`CountingCurve`], a curve that
+//! forwards every operation to the real one and counts point additions,
+//! scalar multiplications and scalar inversions as Givre performs them:
+//! nonce commitments, VSS share checks and Lagrange coefficients included.
+//! Multi-scalar multiplications count one scalar multiplication per term.
+//! Keygen and signing run under [`Counting`], a ciphersuite on the counting
+//! curve that forwards every call to the real one and counts its hashes,
+//! with the keygen transcript hash wrapped in [`CountingDigest`]. Counters are per thread and every party runs on its own
+//! thread (see [`super::local`]), so each party's counts are its own; the
+//! sinks mark where each round's messages go out.
+
+use std::cell::RefCell;
+use std::fmt::Write as _;
+use std::marker::PhantomData;
+
+use generic_ec::core::{
+    Additive, CompressedEncoding, CurveGenerator, Decode, IntegerEncoding, Invertible,
+    Multiplicative, OnCurve, One, Reduce, Samplable, SmallFactor, UncompressedEncoding, Zero,
+};
+use generic_ec::errors::{InvalidPoint, InvalidScalar};
+use generic_ec::multiscalar::MultiscalarMul;
+use generic_ec::{Curve, Point, Scalar};
+use givre::ciphersuite::{Ciphersuite, NormalizedPoint};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::digest::{
+    FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update,
+};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+use zeroize::Zeroize;
+
+use super::ciphersuite::{run_frost_signing_with_ciphersuite, FrostCiphersuite};
+use super::keygen::{self, run_frost_keygen_with_options, KeygenDigest, KeygenOptions};
+use super::local::{run_keygen, run_parties};
+use super::signing;
+
+/// Expensive operations of one party in one round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct OpCounts {
+    /// Multiplications of a point or the generator by a scalar, and one per
+    /// term of a multi-scalar multiplication
+    pub scalar_mults: u64,
+    /// Point additions, subtractions and doublings, and the additions
+    /// combining the terms of multi-scalar multiplications
+    pub point_adds: u64,
+    /// Scalar inversions, e.g. for Lagrange coefficients
+    pub inversions: u64,
+    /// Hash function invocations
+    pub hashes: u64,
+}
+
+impl std::ops::Add for OpCounts {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            scalar_mults: self.scalar_mults + other.scalar_mults,
+            point_adds: self.point_adds + other.point_adds,
+            inversions: self.inversions + other.inversions,
+            hashes: self.hashes + other.hashes,
+        }
+    }
+}
+
+impl std::ops::Sub for OpCounts {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            scalar_mults: self.scalar_mults - other.scalar_mults,
+            point_adds: self.point_adds - other.point_adds,
+            inversions: self.inversions - other.inversions,
+            hashes: self.hashes - other.hashes,
+        }
+    }
+}
+
+/// Operations of one protocol round.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundOps {
+    /// Round number, from 1
+    pub round: u16,
+    /// "Round N" up to the party's last message of round N, "Output" after
+    /// its last message
+    pub name: String,
+    /// Operations of party 0
+    pub ops: OpCounts,
+}
+
+/// Per-round operation counts of one protocol run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpProfile {
+    /// "keygen" or "signing"
+    pub protocol: String,
+    pub ciphersuite: FrostCiphersuite,
+    pub threshold: u16,
+    pub parties: u16,
+    /// Parties running the protocol: n for keygen, t for signing
+    pub participants: u16,
+    /// Hash function behind `hashes`
+    pub hash_function: String,
+    /// Rounds of party 0
+    pub rounds: Vec<RoundOps>,
+    /// Operations of every participant over the whole run
+    pub party_ops: Vec<OpCounts>,
+}
+
+impl OpProfile {
+    /// Operations of party 0 over all rounds.
+    pub fn per_party(&self) -> OpCounts {
+        total(&self.rounds)
+    }
+
+    /// Operations of all participants, each counted as it ran them.
+    pub fn all_parties(&self) -> OpCounts {
+        self.party_ops
+            .iter()
+            .fold(OpCounts::default(), |total, ops| total + *ops)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize profile: {}", e))
+    }
+
+    /// CSV header matching [`OpProfile::csv_rows`].
+    pub const CSV_HEADER: &'static str = "protocol,ciphersuite,threshold,parties,participants,\
+         hash_function,round,name,scalar_mults,point_adds,inversions,hashes";
+
+    /// One CSV row per round of party 0.
+    pub fn csv_rows(&self) -> String {
+        let mut csv = String::new();
+        for round in &self.rounds {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{},{},{}",
+                self.protocol,
+                self.ciphersuite,
+                self.threshold,
+                self.parties,
+                self.participants,
+                self.hash_function,
+                round.round,
+                round.name,
+                round.ops.scalar_mults,
+                round.ops.point_adds,
+                round.ops.inversions,
+                round.ops.hashes
+            );
+        }
+        csv
+    }
+}
+
+/// Operations counted on one thread.
+#[derive(Default)]
+struct Counter {
+    ops: OpCounts,
+    /// Counts at the last message sent in each round
+    marks: Vec<(u16, OpCounts)>,
+}
+
+thread_local! {
+    static COUNTER: RefCell<Option<Counter>> = const { RefCell::new(None) };
+}
+
+/// Whether operations are being counted on this thread.
+pub(crate) fn is_counting() -> bool {
+    COUNTER.with(|counter| counter.borrow().is_some())
+}
+
+fn count(ops: OpCounts) {
+    COUNTER.with(|counter| {
+        if let Some(counter) = counter.borrow_mut().as_mut() {
+            counter.ops = counter.ops + ops;
+        }
+    });
+}
+
+/// Mark a message of `round` (from 1) as sent, closing the round's counts.
+pub(crate) fn mark_round(round: u16) {
+    COUNTER.with(|counter| {
+        if let Some(counter) = counter.borrow_mut().as_mut() {
+            let ops = counter.ops;
+            match counter.marks.last_mut() {
+                Some((last, mark)) if *last == round => *mark = ops,
+                _ => counter.marks.push((round, ops)),
+            }
+        }
+    });
+}
+
+/// Run `fut` with operations counted on this thread and return its output
+/// with the counts of every round.
+async fn counted<T>(fut: impl std::future::Future<Output = T>) -> (T, Vec<RoundOps>) {
+    COUNTER.with(|counter| *counter.borrow_mut() = Some(Counter::default()));
+    let output = fut.await;
+    let counter = COUNTER
+        .with(|counter| counter.borrow_mut().take())
+        .unwrap_or_default();
+
+    let mut rounds = Vec::new();
+    let mut closed = OpCounts::default();
+    for (round, mark) in counter.marks {
+        rounds.push(RoundOps {
+            round,
+            name: format!("Round {}", round),
+            ops: mark - closed,
+        });
+        closed = mark;
+    }
+    rounds.push(RoundOps {
+        round: rounds.last().map_or(1, |r| r.round + 1),
+        name: "Output".to_string(),
+        ops: counter.ops - closed,
+    });
+    (output, rounds)
+}
+
+/// Digest `D` with every finalized hash counted.
+#[derive(Clone, Default)]
+pub(crate) struct CountingDigest<D>(D);
+
+impl<D: HashMarker> HashMarker for CountingDigest<D> {}
+
+impl<D: OutputSizeUser> OutputSizeUser for CountingDigest<D> {
+    type OutputSize = D::OutputSize;
+}
+
+impl<D: Update> Update for CountingDigest<D> {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+}
+
+impl<D: FixedOutput> FixedOutput for CountingDigest<D> {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        count(OpCounts {
+            hashes: 1,
+            ..Default::default()
+        });
+        self.0.finalize_into(out);
+    }
+}
+
+impl<D: Reset> Reset for CountingDigest<D> {
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+}
+
+impl<D: FixedOutputReset> FixedOutputReset for CountingDigest<D> {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        count(OpCounts {
+            hashes: 1,
+            ..Default::default()
+        });
+        self.0.finalize_into_reset(out);
+    }
+}
+
+/// Curve `E` with its point additions, scalar multiplications and scalar
+/// inversions counted.
+///
+/// Points and scalars encode as those of `E` and the curve has the same name,
+/// so key shares move between the two by serialization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub(crate) struct CountingCurve<E>(PhantomData<E>);
+
+impl<E: Curve> Curve for CountingCurve<E> {
+    const CURVE_NAME: &'static str = E::CURVE_NAME;
+
+    type Point = CountingPoint<E>;
+    type Scalar = CountingScalar<E>;
+
+    type CompressedPointArray = E::CompressedPointArray;
+    type UncompressedPointArray = E::UncompressedPointArray;
+    type ScalarArray = E::ScalarArray;
+    type CoordinateArray = E::CoordinateArray;
+}
+
+/// Point of [`CountingCurve`].
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct CountingPoint<E: Curve>(E::Point);
+
+/// Scalar of [`CountingCurve`].
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct CountingScalar<E: Curve>(E::Scalar);
+
+impl<E: Curve> Additive for CountingPoint<E> {
+    fn add(a: &Self, b: &Self) -> Self {
+        count(OpCounts {
+            point_adds: 1,
+            ..Default::default()
+        });
+        Self(Additive::add(&a.0, &b.0))
+    }
+
+    fn sub(a: &Self, b: &Self) -> Self {
+        count(OpCounts {
+            point_adds: 1,
+            ..Default::default()
+        });
+        Self(Additive::sub(&a.0, &b.0))
+    }
+
+    fn negate(x: &Self) -> Self {
+        Self(Additive::negate(&x.0))
+    }
+
+    fn double(x: &Self) -> Self {
+        count(OpCounts {
+            point_adds: 1,
+            ..Default::default()
+        });
+        Self(Additive::double(&x.0))
+    }
+}
+
+impl<E: Curve> From<CurveGenerator> for CountingPoint<E> {
+    fn from(generator: CurveGenerator) -> Self {
+        Self(E::Point::from(generator))
+    }
+}
+
+impl<E: Curve> Zero for CountingPoint<E> {
+    fn zero() -> Self {
+        Self(Zero::zero())
+    }
+
+    fn is_zero(x: &Self) -> Choice {
+        Zero::is_zero(&x.0)
+    }
+}
+
+impl<E: Curve> Zeroize for CountingPoint<E> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<E: Curve> OnCurve for CountingPoint<E> {
+    fn is_on_curve(&self) -> Choice {
+        self.0.is_on_curve()
+    }
+}
+
+impl<E: Curve> SmallFactor for CountingPoint<E> {
+    fn is_torsion_free(&self) -> Choice {
+        self.0.is_torsion_free()
+    }
+}
+
+impl<E: Curve> ConstantTimeEq for CountingPoint<E> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl<E: Curve> ConditionallySelectable for CountingPoint<E> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(E::Point::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl<E: Curve> CompressedEncoding for CountingPoint<E> {
+    type Bytes = E::CompressedPointArray;
+
+    fn to_bytes_compressed(&self) -> Self::Bytes {
+        self.0.to_bytes_compressed()
+    }
+}
+
+impl<E: Curve> UncompressedEncoding for CountingPoint<E> {
+    type Bytes = E::UncompressedPointArray;
+
+    fn to_bytes_uncompressed(&self) -> Self::Bytes {
+        self.0.to_bytes_uncompressed()
+    }
+}
+
+impl<E: Curve> Decode for CountingPoint<E> {
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        E::Point::decode(bytes).map(Self)
+    }
+}
+
+impl<E: Curve> Additive for CountingScalar<E> {
+    fn add(a: &Self, b: &Self) -> Self {
+        Self(Additive::add(&a.0, &b.0))
+    }
+
+    fn sub(a: &Self, b: &Self) -> Self {
+        Self(Additive::sub(&a.0, &b.0))
+    }
+
+    fn negate(x: &Self) -> Self {
+        Self(Additive::negate(&x.0))
+    }
+}
+
+impl<E: Curve> Multiplicative<CountingScalar<E>> for CountingScalar<E> {
+    type Output = Self;
+
+    fn mul(a: &Self, b: &Self) -> Self {
+        Self(Multiplicative::mul(&a.0, &b.0))
+    }
+}
+
+impl<E: Curve> Multiplicative<CurveGenerator> for CountingScalar<E> {
+    type Output = CountingPoint<E>;
+
+    fn mul(a: &Self, generator: &CurveGenerator) -> CountingPoint<E> {
+        count(OpCounts {
+            scalar_mults: 1,
+            ..Default::default()
+        });
+        CountingPoint(Multiplicative::mul(&a.0, generator))
+    }
+}
+
+impl<E: Curve> Multiplicative<CountingPoint<E>> for CountingScalar<E> {
+    type Output = CountingPoint<E>;
+
+    fn mul(a: &Self, point: &CountingPoint<E>) -> CountingPoint<E> {
+        count(OpCounts {
+            scalar_mults: 1,
+            ..Default::default()
+        });
+        CountingPoint(Multiplicative::mul(&a.0, &point.0))
+    }
+}
+
+impl<E: Curve> Invertible for CountingScalar<E> {
+    fn invert(x: &Self) -> CtOption<Self> {
+        count(OpCounts {
+            inversions: 1,
+            ..Default::default()
+        });
+        Invertible::invert(&x.0).map(Self)
+    }
+}
+
+impl<E: Curve> Zero for CountingScalar<E> {
+    fn zero() -> Self {
+        Self(Zero::zero())
+    }
+
+    fn is_zero(x: &Self) -> Choice {
+        Zero::is_zero(&x.0)
+    }
+}
+
+impl<E: Curve> One for CountingScalar<E> {
+    fn one() -> Self {
+        Self(One::one())
+    }
+
+    fn is_one(x: &Self) -> Choice {
+        One::is_one(&x.0)
+    }
+}
+
+impl<E: Curve> Samplable for CountingScalar<E> {
+    fn random<R: RngCore>(rng: &mut R) -> Self {
+        Self(Samplable::random(rng))
+    }
+}
+
+impl<E: Curve> Zeroize for CountingScalar<E> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<E: Curve> ConstantTimeEq for CountingScalar<E> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl<E: Curve> ConditionallySelectable for CountingScalar<E> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(E::Scalar::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl<E: Curve> IntegerEncoding for CountingScalar<E> {
+    type Bytes = E::ScalarArray;
+
+    fn to_be_bytes(&self) -> Self::Bytes {
+        self.0.to_be_bytes()
+    }
+
+    fn to_le_bytes(&self) -> Self::Bytes {
+        self.0.to_le_bytes()
+    }
+
+    fn from_be_bytes_exact(bytes: &Self::Bytes) -> Option<Self> {
+        E::Scalar::from_be_bytes_exact(bytes).map(Self)
+    }
+
+    fn from_le_bytes_exact(bytes: &Self::Bytes) -> Option<Self> {
+        E::Scalar::from_le_bytes_exact(bytes).map(Self)
+    }
+
+    fn from_be_bytes_mod_order(bytes: &[u8]) -> Self {
+        Self(E::Scalar::from_be_bytes_mod_order(bytes))
+    }
+
+    fn from_le_bytes_mod_order(bytes: &[u8]) -> Self {
+        Self(E::Scalar::from_le_bytes_mod_order(bytes))
+    }
+}
+
+impl<E: Curve, const N: usize> Reduce<N> for CountingScalar<E>
+where
+    E::Scalar: Reduce<N>,
+{
+    fn from_be_array_mod_order(bytes: &[u8; N]) -> Self {
+        Self(E::Scalar::from_be_array_mod_order(bytes))
+    }
+
+    fn from_le_array_mod_order(bytes: &[u8; N]) -> Self {
+        Self(E::Scalar::from_le_array_mod_order(bytes))
+    }
+}
+
+/// `point` as a point of `E`.
+fn uncounted_point<E: Curve>(point: &Point<CountingCurve<E>>) -> Point<E> {
+    Point::from_raw(point.as_raw().0)
+        .unwrap_or_else(|_| unreachable!("both curves have the same points"))
+}
+
+/// `point` as a point of [`CountingCurve`].
+fn counted_point<E: Curve>(point: &Point<E>) -> Point<CountingCurve<E>> {
+    Point::from_raw(CountingPoint(*point.as_raw()))
+        .unwrap_or_else(|_| unreachable!("both curves have the same points"))
+}
+
+/// `scalar` as a scalar of `E`.
+fn uncounted_scalar<E: Curve>(scalar: &Scalar<CountingCurve<E>>) -> Scalar<E> {
+    Scalar::from_raw(scalar.as_raw().0)
+}
+
+/// `scalar` as a scalar of [`CountingCurve`].
+fn counted_scalar<E: Curve>(scalar: &Scalar<E>) -> Scalar<CountingCurve<E>> {
+    Scalar::from_raw(CountingScalar(*scalar.as_raw()))
+}
+
+/// Multi-scalar multiplication `M` with its terms counted.
+///
+/// Runs `M` on the uncounted curve, so only the terms are counted and not
+/// the operations of the algorithm `M` combines them with.
+pub(crate) struct CountingMultiscalar<M>(PhantomData<M>);
+
+impl<E: Curve, M: MultiscalarMul<E>> MultiscalarMul<CountingCurve<E>> for CountingMultiscalar<M> {
+    fn multiscalar_mul<S, P>(
+        scalar_points: impl ExactSizeIterator<Item = (S, P)>,
+    ) -> Point<CountingCurve<E>>
+    where
+        S: AsRef<Scalar<CountingCurve<E>>>,
+        P: AsRef<Point<CountingCurve<E>>>,
+    {
+        let terms = scalar_points.len() as u64;
+        count(OpCounts {
+            scalar_mults: terms,
+            point_adds: terms.saturating_sub(1),
+            ..Default::default()
+        });
+        counted_point(&M::multiscalar_mul(scalar_points.map(|(s, p)| {
+            (uncounted_scalar(s.as_ref()), uncounted_point(p.as_ref()))
+        })))
+    }
+}
+
+/// Ciphersuite `C` on [`CountingCurve`], with its hashes counted.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Counting<C>(PhantomData<C>);
+
+/// `point` normalized under ciphersuite `C`, as it is under [`Counting`].
+fn uncounted_normalized<C, P>(
+    point: &NormalizedPoint<Counting<C>, P>,
+) -> NormalizedPoint<C, Point<C::Curve>>
+where
+    C: Ciphersuite,
+    P: AsRef<Point<CountingCurve<C::Curve>>>,
+{
+    NormalizedPoint::try_normalize(uncounted_point(point.as_ref()))
+        .unwrap_or_else(|_| unreachable!("both ciphersuites normalize points alike"))
+}
+
+/// `point` normalized under [`Counting`], as it is under ciphersuite `C`.
+fn counted_normalized<C, P>(
+    point: &NormalizedPoint<C, P>,
+) -> NormalizedPoint<Counting<C>, Point<CountingCurve<C::Curve>>>
+where
+    C: Ciphersuite,
+    P: AsRef<Point<C::Curve>>,
+{
+    NormalizedPoint::try_normalize(counted_point(point.as_ref()))
+        .unwrap_or_else(|_| unreachable!("both ciphersuites normalize points alike"))
+}
+
+impl<C: Ciphersuite> Ciphersuite for Counting<C> {
+    const NAME: &'static str = C::NAME;
+
+    type Curve = CountingCurve<C::Curve>;
+    type Digest = CountingDigest<C::Digest>;
+    type MultiscalarMul = CountingMultiscalar<C::MultiscalarMul>;
+
+    fn h1(msg: &[&[u8]]) -> Scalar<Self::Curve> {
+        count(OpCounts {
+            hashes: 1,
+            ..Default::default()
+        });
+        counted_scalar(&C::h1(msg))
+    }
+
+    fn compute_challenge(
+        group_commitment: &NormalizedPoint<Self, Point<Self::Curve>>,
+        group_public_key: &NormalizedPoint<Self, Point<Self::Curve>>,
+        msg: &[u8],
+    ) -> Scalar<Self::Curve> {
+        count(OpCounts {
+            hashes: 1,
+            ..Default::default()
+        });
+        counted_scalar(&C::compute_challenge(
+            &uncounted_normalized(group_commitment),
+            &uncounted_normalized(group_public_key),
+            msg,
+        ))
+    }
+
+    fn h3(msg: &[&[u8]]) -> Scalar<Self::Curve> {
+        count(OpCounts {
+            hashes: 1,
+            ..Default::default()
+        });
+        counted_scalar(&C::h3(msg))
+    }
+
+    fn h4() -> Self::Digest {
+        CountingDigest(C::h4())
+    }
+
+    fn h5() -> Self::Digest {
+        CountingDigest(C::h5())
+    }
+
+    type PointBytes = C::PointBytes;
+
+    fn serialize_point(point: &Point<Self::Curve>) -> Self::PointBytes {
+        C::serialize_point(&uncounted_point(point))
+    }
+
+    fn deserialize_point(bytes: &[u8]) -> Result<Point<Self::Curve>, InvalidPoint> {
+        C::deserialize_point(bytes).map(|point| counted_point(&point))
+    }
+
+    type ScalarBytes = C::ScalarBytes;
+
+    fn serialize_scalar(scalar: &Scalar<Self::Curve>) -> Self::ScalarBytes {
+        C::serialize_scalar(&uncounted_scalar(scalar))
+    }
+
+    fn deserialize_scalar(bytes: &[u8]) -> Result<Scalar<Self::Curve>, InvalidScalar> {
+        C::deserialize_scalar(bytes).map(|scalar| counted_scalar(&scalar))
+    }
+
+    type NormalizedPointBytes = C::NormalizedPointBytes;
+
+    fn serialize_normalized_point<P: AsRef<Point<Self::Curve>>>(
+        point: &NormalizedPoint<Self, P>,
+    ) -> Self::NormalizedPointBytes {
+        C::serialize_normalized_point(&uncounted_normalized(point))
+    }
+
+    fn deserialize_normalized_point(
+        bytes: &[u8],
+    ) -> Result<NormalizedPoint<Self, Point<Self::Curve>>, InvalidPoint> {
+        C::deserialize_normalized_point(bytes).map(|point| counted_normalized(&point))
+    }
+
+    fn is_normalized(point: &Point<Self::Curve>) -> bool {
+        C::is_normalized(&uncounted_point(point))
+    }
+
+    fn normalize_point(point: Point<Self::Curve>) -> NormalizedPoint<Self, Point<Self::Curve>> {
+        counted_normalized(&C::normalize_point(uncounted_point(&point)))
+    }
+}
+
+/// Count the operations of a `threshold`-of-`parties` keygen under
+/// `options`.
+///
+/// The "Output" round includes signing the keygen transcript.
+pub fn profile_keygen_ops(
+    threshold: u16,
+    parties: u16,
+    options: &KeygenOptions,
+) -> Result<OpProfile, String> {
+    let session_id = format!("ops-keygen-{}-of-{}", threshold, parties);
+    let runs = run_parties(
+        parties,
+        |m: &keygen::ProtocolMessage| (m.sender, m.recipient),
+        |i, rx, tx| {
+            counted(run_frost_keygen_with_options(
+                i,
+                parties,
+                threshold,
+                &session_id,
+                options,
+                rx,
+                tx,
+            ))
+        },
+    );
+    let mut rounds = Vec::new();
+    let mut party_ops = Vec::new();
+    for (i, (result, party_rounds)) in runs.into_iter().enumerate() {
+        if !result.success {
+            return Err(format!(
+                "Keygen of party {} failed: {}",
+                i,
+                result.error.unwrap_or_default()
+            ));
+        }
+        party_ops.push(total(&party_rounds));
+        if i == 0 {
+            rounds = party_rounds;
+        }
+    }
+
+    Ok(OpProfile {
+        protocol: "keygen".to_string(),
+        ciphersuite: options.ciphersuite,
+        threshold,
+        parties,
+        participants: parties,
+        hash_function: match options.digest {
+            KeygenDigest::Sha256 => "SHA-256",
+            KeygenDigest::Sha512 => "SHA-512",
+        }
+        .to_string(),
+        rounds,
+        party_ops,
+    })
+}
+
+/// Count the operations of FROST signing by the first `threshold` of
+/// `parties` key holders, after an uncounted keygen.
+pub fn profile_signing_ops(
+    threshold: u16,
+    parties: u16,
+    ciphersuite: FrostCiphersuite,
+) -> Result<OpProfile, String> {
+    let options = KeygenOptions {
+        ciphersuite,
+        ..Default::default()
+    };
+    let key_shares = run_keygen(threshold, parties, &options)
+        .into_iter()
+        .enumerate()
+        .map(|(i, result)| {
+            result.key_share_data.ok_or_else(|| {
+                format!(
+                    "Keygen of party {} failed: {}",
+                    i,
+                    result.error.unwrap_or_default()
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let signers: Vec<u16> = (0..threshold).collect();
+    let message = [0x42; 32];
+    let runs = run_parties(
+        threshold,
+        |m: &signing::ProtocolMessage| (m.sender, m.recipient),
+        |i, rx, tx| {
+            counted(run_frost_signing_with_ciphersuite(
+                i,
+                &signers,
+                "ops-signing",
+                &message,
+                &key_shares[usize::from(signers[usize::from(i)])],
+                ciphersuite,
+                rx,
+                tx,
+            ))
+        },
+    );
+    let mut rounds = Vec::new();
+    let mut party_ops = Vec::new();
+    for (i, (result, party_rounds)) in runs.into_iter().enumerate() {
+        if !result.success {
+            return Err(format!(
+                "Signing of party {} failed: {}",
+                i,
+                result.error.unwrap_or_default()
+            ));
+        }
+        party_ops.push(total(&party_rounds));
+        if i == 0 {
+            rounds = party_rounds;
+        }
+    }
+
+    Ok(OpProfile {
+        protocol: "signing".to_string(),
+        ciphersuite,
+        threshold,
+        parties,
+        participants: threshold,
+        hash_function: match ciphersuite {
+            FrostCiphersuite::Bitcoin | FrostCiphersuite::Secp256k1 => "SHA-256",
+            FrostCiphersuite::Ed25519 | FrostCiphersuite::Ristretto255 => "SHA-512",
+        }
+        .to_string(),
+        rounds,
+        party_ops,
+    })
+}
+
+fn total(rounds: &[RoundOps]) -> OpCounts {
+    rounds
+        .iter()
+        .fold(OpCounts::default(), |total, round| total + round.ops)
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_ec::curves::Ed25519;
+
+    use super::*;
+
+    #[test]
+    fn test_counting_curve_counts_each_operation() {
+        let ((), rounds) = futures::executor::block_on(counted(async {
+            let x = Scalar::<CountingCurve<Ed25519>>::from(7u64);
+            let p = Point::generator() * x;
+            let q = p * x + p;
+            let inverse = x.invert().unwrap();
+            assert_eq!(
+                uncounted_point(&(q * inverse)),
+                uncounted_point(&(p + p * inverse))
+            );
+            assert_eq!(
+                uncounted_point(&p),
+                Point::<Ed25519>::generator() * uncounted_scalar(&x)
+            );
+        }));
+        assert_eq!(
+            total(&rounds),
+            OpCounts {
+                scalar_mults: 4,
+                point_adds: 2,
+                inversions: 1,
+                hashes: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_op_counts_grow_with_threshold_and_parties() {
+        let options = KeygenOptions {
+            ciphersuite: FrostCiphersuite::Ed25519,
+            ..Default::default()
+        };
+        let keygen_2_of_2 = profile_keygen_ops(2, 2, &options).unwrap();
+        let keygen_2_of_3 = profile_keygen_ops(2, 3, &options).unwrap();
+        assert_eq!(keygen_2_of_3.party_ops.len(), 3);
+        assert!(keygen_2_of_3.per_party().hashes > keygen_2_of_2.per_party().hashes);
+        // Every received share is checked against its sender's commitments
+        assert!(keygen_2_of_3.per_party().scalar_mults > keygen_2_of_2.per_party().scalar_mults);
+        assert!(keygen_2_of_3.per_party().point_adds > keygen_2_of_2.per_party().point_adds);
+        assert!(keygen_2_of_3.all_parties().hashes > keygen_2_of_3.per_party().hashes);
+        assert_eq!(keygen_2_of_3.hash_function, "SHA-256");
+
+        let signing_2_of_3 = profile_signing_ops(2, 3, FrostCiphersuite::Ed25519).unwrap();
+        let signing_3_of_3 = profile_signing_ops(3, 3, FrostCiphersuite::Ed25519).unwrap();
+        assert_eq!(signing_2_of_3.party_ops.len(), 2);
+        assert!(signing_3_of_3.per_party().hashes > signing_2_of_3.per_party().hashes);
+        assert!(signing_3_of_3.per_party().scalar_mults > signing_2_of_3.per_party().scalar_mults);
+        // Lagrange coefficients of the signers
+        assert!(signing_2_of_3.per_party().inversions > 0);
+        // At least the two nonce commitments
+        assert!(signing_2_of_3.rounds[0].ops.scalar_mults >= 2);
+        assert_eq!(
+            signing_2_of_3.rounds.last().map(|r| r.name.as_str()),
+            Some("Output")
+        );
+        assert!(signing_2_of_3
+            .csv_rows()
+            .starts_with("signing,ed25519,2,3,2,SHA-512,1,Round 1,"));
+    }
+}
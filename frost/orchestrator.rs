@@ -0,0 +1,571 @@
+//! Signer-subset selection and retry for coordinated FROST signing.
+//!
+//! `run_frost_coordinator` signs with a fixed set of signers and fails or
+//! hangs if one of them misbehaves or goes offline. The orchestrator knows
+//! all n parties instead. For every attempt it probes which parties are
+//! live, picks t of them and runs the coordinator under a timeout. Parties
+//! blamed for an invalid partial signature and signers that stopped
+//! answering in a timed-out attempt are excluded for good, parties in any
+//! other failed attempt are tried last. Signing gives up as soon as fewer
+//! than t parties remain, or when `max_attempts` is reached.
+//!
+//! Attempt `a` uses two relay sessions:
+//! - `{session_id}:{a}`: round 1 liveness probe, round 2 invitation
+//! - `{session_id}:{a}:sign`: coordinated signing, see [`super::coordinator`]
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use async_channel::{Receiver, Sender};
+use futures::future::{self, Either};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use super::coordinator::{run_frost_coordinator, COORDINATOR_INDEX};
+use super::rounds::{with_timeout, RoundTransport};
+use super::signing::{ProtocolMessage, SchnorrSignature, SigningOptions};
+
+/// Liveness probe sent to every candidate signer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LivenessProbe {
+    message_hash: [u8; 32],
+}
+
+/// Tells every live party which signers were picked for an attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SigningInvitation {
+    signing_session_id: String,
+    signers: Vec<u16>,
+}
+
+/// Parties and timeouts for orchestrated signing.
+#[derive(Debug, Clone)]
+pub struct OrchestratorConfig {
+    /// Keygen indices of all n parties
+    pub parties: Vec<u16>,
+    /// Number of signers required
+    pub threshold: u16,
+    /// How long to wait for liveness probe answers
+    pub probe_timeout: Duration,
+    /// How long one signing attempt may take
+    pub attempt_timeout: Duration,
+    /// Upper bound on the number of signing attempts
+    pub max_attempts: usize,
+}
+
+/// Result of orchestrated FROST signing.
+#[derive(Debug)]
+pub struct FrostOrchestratedSigningResult {
+    pub success: bool,
+    /// Aggregated signature, present on success
+    pub signature: Option<SchnorrSignature>,
+    /// The x-only key the signature verifies against
+    pub output_key: Option<[u8; 32]>,
+    /// Signers (index at keygen) of the successful attempt
+    pub signers: Vec<u16>,
+    /// Number of signing attempts made
+    pub attempts: usize,
+    /// Parties excluded for sending invalid partial signatures
+    pub blamed_parties: Vec<u16>,
+    /// Parties excluded for not answering within a signing attempt
+    pub timed_out_parties: Vec<u16>,
+    /// Error message if failed
+    pub error: Option<String>,
+    /// Duration of the protocol
+    pub duration_secs: f64,
+}
+
+/// Pick `threshold` signers from `live`, preferring parties that were in
+/// the fewest timed-out or failed attempts.
+///
+/// Returns `None` if fewer than `threshold` parties are live.
+pub fn select_signers(
+    live: &[u16],
+    threshold: u16,
+    timeouts: &BTreeMap<u16, usize>,
+) -> Option<Vec<u16>> {
+    if live.len() < usize::from(threshold) {
+        return None;
+    }
+    let mut ranked = live.to_vec();
+    ranked.sort_by_key(|p| (timeouts.get(p).copied().unwrap_or(0), *p));
+    let mut signers = ranked[..usize::from(threshold)].to_vec();
+    signers.sort_unstable();
+    Some(signers)
+}
+
+/// Signers that had not sent their message of the round the coordinator was
+/// waiting for, given the (round, sender) pairs that did arrive.
+fn unresponsive_signers(signers: &[u16], arrived: &BTreeSet<(u16, u16)>) -> Vec<u16> {
+    // Rounds in which the coordinator receives from every signer
+    [1, 3]
+        .into_iter()
+        .map(|round| {
+            signers
+                .iter()
+                .copied()
+                .filter(|&signer| !arrived.contains(&(round, signer)))
+                .collect::<Vec<u16>>()
+        })
+        .find(|missing| !missing.is_empty())
+        .unwrap_or_default()
+}
+
+/// Sign `message_hash` with any `threshold` of `config.parties`, retrying
+/// with a different subset on timeout or blamed failure.
+///
+/// `open_session` must return the relay channels for the given session ID,
+/// as for [`super::psbt::sign_psbt_with_frost`]. Signers answer with
+/// [`answer_signing_probe`].
+pub async fn run_frost_orchestrated_signing<F>(
+    config: &OrchestratorConfig,
+    session_id: &str,
+    message_hash: &[u8; 32],
+    key_info_data: &[u8],
+    options: &SigningOptions,
+    mut open_session: F,
+) -> FrostOrchestratedSigningResult
+where
+    F: FnMut(&str) -> (Receiver<ProtocolMessage>, Sender<ProtocolMessage>),
+{
+    let start = std::time::Instant::now();
+
+    info!("========================================");
+    info!("  FROST ORCHESTRATED SIGNING STARTING");
+    info!("========================================");
+    info!("Parties: {:?}", config.parties);
+    info!("Threshold: {}", config.threshold);
+    info!("Session ID: {}", session_id);
+
+    let mut blamed: BTreeSet<u16> = BTreeSet::new();
+    let mut timed_out: BTreeSet<u16> = BTreeSet::new();
+    let mut timeouts: BTreeMap<u16, usize> = BTreeMap::new();
+    let mut attempts = 0;
+    let mut last_error: Option<String> = None;
+
+    let result: Result<(SchnorrSignature, [u8; 32], Vec<u16>), String> = async {
+        loop {
+            let candidates: Vec<u16> = config
+                .parties
+                .iter()
+                .copied()
+                .filter(|p| !blamed.contains(p) && !timed_out.contains(p))
+                .collect();
+            if candidates.len() < usize::from(config.threshold) {
+                return Err(format!(
+                    "Only {} responsive honest parties remain, {} required",
+                    candidates.len(),
+                    config.threshold
+                ));
+            }
+            if attempts >= config.max_attempts {
+                return Err(match last_error.take() {
+                    Some(e) => format!("Giving up after {} signing attempts: {}", attempts, e),
+                    None => format!("Giving up after {} signing attempts", attempts),
+                });
+            }
+            attempts += 1;
+
+            // Probe liveness
+            let probe_session_id = format!("{}:{}", session_id, attempts);
+            let (incoming_rx, outgoing_tx) = open_session(&probe_session_id);
+            let mut transport = RoundTransport::new(
+                &probe_session_id,
+                COORDINATOR_INDEX,
+                incoming_rx,
+                outgoing_tx,
+            );
+            transport
+                .broadcast(
+                    1,
+                    &LivenessProbe {
+                        message_hash: *message_hash,
+                    },
+                )
+                .await?;
+            let live: Vec<u16> = transport
+                .collect_within::<LivenessProbe>(1, &candidates, config.probe_timeout)
+                .await?
+                .into_keys()
+                .collect();
+            info!("Attempt {}: live parties {:?}", attempts, live);
+
+            let selected = select_signers(&live, config.threshold, &timeouts);
+
+            // Invite the chosen signers; an empty invitation releases the
+            // live parties if there are too few of them
+            let signing_session_id = format!("{}:sign", probe_session_id);
+            transport
+                .broadcast(
+                    2,
+                    &SigningInvitation {
+                        signing_session_id: signing_session_id.clone(),
+                        signers: selected.clone().unwrap_or_default(),
+                    },
+                )
+                .await?;
+            let Some(signers) = selected else {
+                let e = format!(
+                    "Only {} parties answered the liveness probe, {} required",
+                    live.len(),
+                    config.threshold
+                );
+                warn!("Attempt {}: {}", attempts, e);
+                last_error = Some(e);
+                continue;
+            };
+
+            // Note every message reaching the coordinator, to tell who it
+            // was still waiting for if the attempt times out
+            let (incoming_rx, outgoing_tx) = open_session(&signing_session_id);
+            let (observed_tx, observed_rx) = async_channel::unbounded();
+            let mut arrived = BTreeSet::new();
+            let observe = async {
+                while let Ok(msg) = incoming_rx.recv().await {
+                    if msg.session_id == signing_session_id {
+                        arrived.insert((msg.round, msg.sender));
+                    }
+                    if observed_tx.send(msg).await.is_err() {
+                        break;
+                    }
+                }
+                drop(observed_tx);
+            };
+            let attempt = with_timeout(
+                run_frost_coordinator(
+                    &signers,
+                    &signing_session_id,
+                    message_hash,
+                    key_info_data,
+                    options,
+                    observed_rx,
+                    outgoing_tx,
+                ),
+                config.attempt_timeout,
+            );
+            let outcome = match future::select(Box::pin(attempt), Box::pin(observe)).await {
+                Either::Left((outcome, _)) => outcome,
+                Either::Right((_, attempt)) => attempt.await,
+            };
+            match outcome {
+                Some(r) if r.success => {
+                    let signature = r.signature.ok_or("Coordinator returned no signature")?;
+                    let output_key = r.output_key.ok_or("Coordinator returned no output key")?;
+                    return Ok((signature, output_key, signers));
+                }
+                Some(r) if !r.blamed_parties.is_empty() => {
+                    warn!(
+                        "Attempt {} failed, excluding blamed parties {:?}",
+                        attempts, r.blamed_parties
+                    );
+                    blamed.extend(r.blamed_parties);
+                }
+                Some(r) => {
+                    let e = r.error.unwrap_or_else(|| "Coordinator failed".to_string());
+                    warn!(
+                        "Attempt {} with signers {:?} failed: {}",
+                        attempts, signers, e
+                    );
+                    for signer in &signers {
+                        *timeouts.entry(*signer).or_insert(0) += 1;
+                    }
+                    last_error = Some(e);
+                }
+                None => {
+                    let unresponsive = unresponsive_signers(&signers, &arrived);
+                    warn!(
+                        "Attempt {} with signers {:?} timed out, excluding unresponsive parties {:?}",
+                        attempts, signers, unresponsive
+                    );
+                    for signer in &signers {
+                        *timeouts.entry(*signer).or_insert(0) += 1;
+                    }
+                    timed_out.extend(unresponsive);
+                    last_error = Some(format!("Attempt {} timed out", attempts));
+                }
+            }
+        }
+    }
+    .await;
+
+    let blamed_parties: Vec<u16> = blamed.into_iter().collect();
+    let timed_out_parties: Vec<u16> = timed_out.into_iter().collect();
+    match result {
+        Ok((signature, output_key, signers)) => {
+            info!(
+                "FROST orchestrated signing completed in {:.2}s after {} attempt(s)",
+                start.elapsed().as_secs_f64(),
+                attempts
+            );
+            FrostOrchestratedSigningResult {
+                success: true,
+                signature: Some(signature),
+                output_key: Some(output_key),
+                signers,
+                attempts,
+                blamed_parties,
+                timed_out_parties,
+                error: None,
+                duration_secs: start.elapsed().as_secs_f64(),
+            }
+        }
+        Err(e) => {
+            error!("FROST orchestrated signing failed: {}", e);
+            FrostOrchestratedSigningResult {
+                success: false,
+                signature: None,
+                output_key: None,
+                signers: Vec::new(),
+                attempts,
+                blamed_parties,
+                timed_out_parties,
+                error: Some(e),
+                duration_secs: start.elapsed().as_secs_f64(),
+            }
+        }
+    }
+}
+
+/// Answer the liveness probe of one orchestrator attempt.
+///
+/// Only answers probes for `message_hash`. Returns the signing session ID
+/// to join with [`super::coordinator::run_frost_coordinated_signer`] if this
+/// party was selected, or `None` otherwise.
+pub async fn answer_signing_probe(
+    party_index: u16,
+    probe_session_id: &str,
+    message_hash: &[u8; 32],
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> Result<Option<String>, String> {
+    let mut transport =
+        RoundTransport::new(probe_session_id, party_index, incoming_rx, outgoing_tx);
+
+    let probe = transport
+        .collect::<LivenessProbe>(1, &[COORDINATOR_INDEX])
+        .await?
+        .remove(&COORDINATOR_INDEX)
+        .ok_or_else(|| "No liveness probe from coordinator".to_string())?;
+    if probe.message_hash != *message_hash {
+        return Err(format!(
+            "Coordinator probed for {}, expected {}",
+            hex::encode(probe.message_hash),
+            hex::encode(message_hash)
+        ));
+    }
+    transport.send(Some(COORDINATOR_INDEX), 1, &probe).await?;
+
+    let invitation = transport
+        .collect::<SigningInvitation>(2, &[COORDINATOR_INDEX])
+        .await?
+        .remove(&COORDINATOR_INDEX)
+        .ok_or_else(|| "No signing invitation from coordinator".to_string())?;
+    Ok(invitation
+        .signers
+        .contains(&party_index)
+        .then_some(invitation.signing_session_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::coordinator::{export_key_info, run_frost_coordinated_signer};
+    use super::super::local;
+    use super::*;
+
+    #[test]
+    fn test_select_signers_prefers_parties_without_timeouts() {
+        let live = [0, 1, 2, 3, 4];
+        let mut timeouts = BTreeMap::new();
+        assert_eq!(select_signers(&live, 3, &timeouts), Some(vec![0, 1, 2]));
+
+        for p in [0, 1, 2] {
+            timeouts.insert(p, 1);
+        }
+        assert_eq!(select_signers(&live, 3, &timeouts), Some(vec![0, 3, 4]));
+        assert_eq!(select_signers(&live[..2], 3, &timeouts), None);
+    }
+
+    #[test]
+    fn test_orchestrator_retries_when_too_few_parties_are_live() {
+        let key_shares = local::key_shares(2, 3);
+        let key_info = export_key_info(&key_shares[0]).unwrap();
+        let message_hash = [0x42u8; 32];
+        let options = SigningOptions::default();
+        let config = OrchestratorConfig {
+            parties: vec![0, 1, 2],
+            threshold: 2,
+            probe_timeout: Duration::from_millis(300),
+            attempt_timeout: Duration::from_secs(10),
+            max_attempts: 3,
+        };
+        let orchestrator = 3;
+
+        let results = local::run_parties(
+            4,
+            |m: &ProtocolMessage| {
+                let slot = |p: u16| {
+                    if p == COORDINATOR_INDEX {
+                        orchestrator
+                    } else {
+                        p
+                    }
+                };
+                (slot(m.sender), m.recipient.map(slot))
+            },
+            |i, rx, tx| {
+                let (key_shares, key_info, options, config) =
+                    (&key_shares, &key_info, &options, &config);
+                async move {
+                    if i == orchestrator {
+                        return Some(
+                            run_frost_orchestrated_signing(
+                                config,
+                                "orchestrated",
+                                &message_hash,
+                                key_info,
+                                options,
+                                |_| (rx.clone(), tx.clone()),
+                            )
+                            .await,
+                        );
+                    }
+                    // Party 1 is offline and party 2 misses the first attempt
+                    let first_attempt = match i {
+                        1 => return None,
+                        2 => 2,
+                        _ => 1,
+                    };
+                    for attempt in first_attempt..=config.max_attempts {
+                        let probe_session_id = format!("orchestrated:{}", attempt);
+                        let answer = answer_signing_probe(
+                            i,
+                            &probe_session_id,
+                            &message_hash,
+                            rx.clone(),
+                            tx.clone(),
+                        );
+                        match with_timeout(answer, Duration::from_secs(2)).await {
+                            Some(Ok(Some(signing_session_id))) => {
+                                let result = run_frost_coordinated_signer(
+                                    &signing_session_id,
+                                    &message_hash,
+                                    &key_shares[usize::from(i)],
+                                    options,
+                                    rx.clone(),
+                                    tx.clone(),
+                                )
+                                .await;
+                                assert!(result.success, "signer failed: {:?}", result.error);
+                            }
+                            Some(Ok(None)) => {}
+                            Some(Err(e)) => panic!("probe failed: {}", e),
+                            None => break,
+                        }
+                    }
+                    None
+                }
+            },
+        );
+
+        let result = results.into_iter().flatten().next().unwrap();
+        assert!(result.success, "orchestration failed: {:?}", result.error);
+        assert_eq!(result.attempts, 2);
+        assert_eq!(result.signers, vec![0, 2]);
+        let output_key = result.output_key.unwrap();
+        assert!(result
+            .signature
+            .unwrap()
+            .verify(&output_key, &message_hash)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_orchestrator_excludes_signers_that_time_out() {
+        let key_shares = local::key_shares(2, 3);
+        let key_info = export_key_info(&key_shares[0]).unwrap();
+        let message_hash = [0x42u8; 32];
+        let options = SigningOptions::default();
+        let config = OrchestratorConfig {
+            parties: vec![0, 1, 2],
+            threshold: 2,
+            probe_timeout: Duration::from_millis(300),
+            attempt_timeout: Duration::from_secs(1),
+            max_attempts: 3,
+        };
+        let orchestrator = 3;
+
+        let results = local::run_parties(
+            4,
+            |m: &ProtocolMessage| {
+                let slot = |p: u16| {
+                    if p == COORDINATOR_INDEX {
+                        orchestrator
+                    } else {
+                        p
+                    }
+                };
+                (slot(m.sender), m.recipient.map(slot))
+            },
+            |i, rx, tx| {
+                let (key_shares, key_info, options, config) =
+                    (&key_shares, &key_info, &options, &config);
+                async move {
+                    if i == orchestrator {
+                        return Some(
+                            run_frost_orchestrated_signing(
+                                config,
+                                "timeouts",
+                                &message_hash,
+                                key_info,
+                                options,
+                                |_| (rx.clone(), tx.clone()),
+                            )
+                            .await,
+                        );
+                    }
+                    for attempt in 1..=config.max_attempts {
+                        let probe_session_id = format!("timeouts:{}", attempt);
+                        let answer = answer_signing_probe(
+                            i,
+                            &probe_session_id,
+                            &message_hash,
+                            rx.clone(),
+                            tx.clone(),
+                        );
+                        let Some(Ok(Some(signing_session_id))) =
+                            with_timeout(answer, Duration::from_secs(3)).await
+                        else {
+                            continue;
+                        };
+                        // Party 1 answers the probe, then goes silent
+                        if i == 1 {
+                            return None;
+                        }
+                        let signer = run_frost_coordinated_signer(
+                            &signing_session_id,
+                            &message_hash,
+                            &key_shares[usize::from(i)],
+                            options,
+                            rx.clone(),
+                            tx.clone(),
+                        );
+                        // Give up on a stalled attempt before the next probe
+                        if let Some(result) = with_timeout(signer, Duration::from_millis(500)).await
+                        {
+                            assert!(result.success, "signer failed: {:?}", result.error);
+                            break;
+                        }
+                    }
+                    None
+                }
+            },
+        );
+
+        let result = results.into_iter().flatten().next().unwrap();
+        assert!(result.success, "orchestration failed: {:?}", result.error);
+        assert_eq!(result.attempts, 2);
+        assert_eq!(result.signers, vec![0, 2]);
+        assert_eq!(result.timed_out_parties, vec![1]);
+        assert!(result.blamed_parties.is_empty());
+    }
+}
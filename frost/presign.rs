@@ -0,0 +1,648 @@
+//! FROST nonce preprocessing (presignatures).
+//!
+//! Round 1 of FROST signing does not depend on the message, so signers can
+//! generate batches of single-use nonce commitments ahead of time and
+//! exchange them. When a message arrives, signing is a single round: each
+//! signer consumes one stored nonce, broadcasts its partial signature and
+//! aggregates.
+//!
+//! A presignature is identified by a hash of the session ID and the
+//! commitments of every signer, so all signers derive the same ID from what
+//! they agreed on in preprocessing, however many presignatures each of them
+//! generated before.
+//!
+//! A store created with [`PresignatureStore::new`] lives in memory and its
+//! presignatures are lost on restart. [`PresignatureStore::open`] keeps them
+//! in a file instead, which holds secret nonces and must be protected like
+//! the key share itself.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use async_channel::{Receiver, Sender};
+use generic_ec::{Scalar, SecretScalar};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use super::ciphersuite::{load_key_share, FrostCiphersuite};
+use super::message::tagged_hash;
+use super::nonce_ledger::sync_parent_dir;
+use super::rounds::{
+    aggregate_with_blame, commit, sign_share, signing_tweak, FrostCurve, PartialSignature,
+    PublicCommitments, RoundTransport, SecretNonces,
+};
+use super::signing::{
+    verifying_key, FrostKeyShare, FrostSigningResult, ProtocolMessage, SigningOptions,
+};
+use super::transcript::SigningTranscript;
+use crate::bench::BenchmarkRecorder;
+
+/// Identifier of a presignature, agreed on by all of its signers.
+pub type PresignatureId = [u8; 32];
+
+/// A preprocessed nonce and the commitments of every signer it was agreed with.
+struct Presignature {
+    nonces: SecretNonces,
+    /// Commitments by index at keygen, in keygen order
+    commitments: Vec<(u16, PublicCommitments)>,
+}
+
+/// A presignature as stored on disk.
+#[derive(Serialize, Deserialize)]
+struct StoredPresignature {
+    /// Big-endian hiding nonce (hex)
+    hiding_nonce: String,
+    /// Big-endian binding nonce (hex)
+    binding_nonce: String,
+    commitments: Vec<(u16, PublicCommitments)>,
+}
+
+impl From<&Presignature> for StoredPresignature {
+    fn from(presignature: &Presignature) -> Self {
+        let nonces = &presignature.nonces;
+        Self {
+            hiding_nonce: hex::encode(nonces.hiding_nonce.as_ref().to_be_bytes()),
+            binding_nonce: hex::encode(nonces.binding_nonce.as_ref().to_be_bytes()),
+            commitments: presignature.commitments.clone(),
+        }
+    }
+}
+
+impl TryFrom<StoredPresignature> for Presignature {
+    type Error = String;
+
+    fn try_from(stored: StoredPresignature) -> Result<Self, String> {
+        let nonce = |encoded: &str| -> Result<SecretScalar<FrostCurve>, String> {
+            let bytes = hex::decode(encoded).map_err(|e| format!("Invalid nonce: {}", e))?;
+            let mut scalar = Scalar::<FrostCurve>::from_be_bytes(&bytes)
+                .map_err(|e| format!("Invalid nonce: {}", e))?;
+            Ok(SecretScalar::new(&mut scalar))
+        };
+        Ok(Self {
+            nonces: SecretNonces {
+                hiding_nonce: nonce(&stored.hiding_nonce)?,
+                binding_nonce: nonce(&stored.binding_nonce)?,
+            },
+            commitments: stored.commitments,
+        })
+    }
+}
+
+/// Per-party store of preprocessed nonces and everyone's commitments.
+#[derive(Default)]
+pub struct PresignatureStore {
+    presignatures: BTreeMap<PresignatureId, Presignature>,
+    /// File the store is persisted to, if any
+    path: Option<PathBuf>,
+}
+
+impl PresignatureStore {
+    /// Empty in-memory store. Its presignatures do not survive a restart.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the store persisted at `path`, creating it if needed.
+    ///
+    /// Every change is written to a temporary file, fsync'd and renamed over
+    /// `path` before it takes effect, so a presignature taken for signing is
+    /// gone from disk before its nonce is used.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let mut presignatures = BTreeMap::new();
+        if path.exists() {
+            let contents = std::fs::read(&path).map_err(|e| {
+                format!(
+                    "Failed to read presignature store {}: {}",
+                    path.display(),
+                    e
+                )
+            })?;
+            let stored: BTreeMap<String, StoredPresignature> = serde_json::from_slice(&contents)
+                .map_err(|e| format!("Corrupt presignature store {}: {}", path.display(), e))?;
+            for (id, presignature) in stored {
+                let id: PresignatureId = hex::decode(&id)
+                    .ok()
+                    .and_then(|id| id.try_into().ok())
+                    .ok_or_else(|| format!("Invalid presignature ID {}", id))?;
+                presignatures.insert(id, presignature.try_into()?);
+            }
+        }
+
+        let store = Self {
+            presignatures,
+            path: Some(path),
+        };
+        store.persist()?;
+        Ok(store)
+    }
+
+    /// Number of unused presignatures this party still holds.
+    pub fn available(&self) -> usize {
+        self.presignatures.len()
+    }
+
+    /// IDs of unused presignatures.
+    pub fn available_ids(&self) -> Vec<PresignatureId> {
+        self.presignatures.keys().copied().collect()
+    }
+
+    /// Store a presignature agreed in `session_id` under its ID.
+    fn insert(
+        &mut self,
+        session_id: &str,
+        nonces: SecretNonces,
+        commitments: Vec<(u16, PublicCommitments)>,
+    ) -> Result<PresignatureId, String> {
+        let id = presignature_id(session_id, &commitments)?;
+        if self.presignatures.contains_key(&id) {
+            return Err(format!("Presignature {} already exists", hex::encode(id)));
+        }
+        self.presignatures.insert(
+            id,
+            Presignature {
+                nonces,
+                commitments,
+            },
+        );
+        if let Err(e) = self.persist() {
+            self.presignatures.remove(&id);
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    /// Remove and return presignature `id`. Each ID can be taken once.
+    ///
+    /// If the removal cannot be persisted the presignature is dropped and an
+    /// error returned, so its nonce is never used.
+    fn take(&mut self, id: &PresignatureId) -> Result<Option<Presignature>, String> {
+        let presignature = self.presignatures.remove(id);
+        if presignature.is_some() {
+            self.persist()?;
+        }
+        Ok(presignature)
+    }
+
+    /// Atomically write all presignatures to the store file, if any.
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let stored: BTreeMap<String, StoredPresignature> = self
+            .presignatures
+            .iter()
+            .map(|(id, presignature)| (hex::encode(id), presignature.into()))
+            .collect();
+        let contents = serde_json::to_vec(&stored)
+            .map_err(|e| format!("Failed to serialize presignatures: {}", e))?;
+
+        let tmp = path.with_extension("tmp");
+        File::create(&tmp)
+            .and_then(|mut f| {
+                f.write_all(&contents)?;
+                f.sync_all()
+            })
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .and_then(|_| sync_parent_dir(path))
+            .map_err(|e| {
+                format!(
+                    "Failed to persist presignature store {}: {}",
+                    path.display(),
+                    e
+                )
+            })
+    }
+}
+
+/// ID of the presignature with `commitments`, agreed in `session_id`.
+fn presignature_id(
+    session_id: &str,
+    commitments: &[(u16, PublicCommitments)],
+) -> Result<PresignatureId, String> {
+    let mut data = (session_id.len() as u64).to_be_bytes().to_vec();
+    data.extend_from_slice(session_id.as_bytes());
+    data.extend(
+        serde_json::to_vec(commitments)
+            .map_err(|e| format!("Failed to serialize commitments: {}", e))?,
+    );
+    Ok(tagged_hash("FROST/presignature", &data))
+}
+
+/// Result of a nonce preprocessing session.
+#[derive(Debug)]
+pub struct FrostPreprocessingResult {
+    pub success: bool,
+    /// IDs of the presignatures now usable by every signer
+    pub presignature_ids: Vec<PresignatureId>,
+    /// Error message if failed
+    pub error: Option<String>,
+    /// Duration of the protocol
+    pub duration_secs: f64,
+}
+
+/// Generate `count` presignatures and exchange commitments with all signers.
+///
+/// Presignatures are only stored once the commitments of every signer have
+/// arrived, and are then usable by exactly the signers in `parties_at_keygen`.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_frost_preprocessing(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    key_share_data: &[u8],
+    count: usize,
+    store: &mut PresignatureStore,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostPreprocessingResult {
+    let start = std::time::Instant::now();
+    info!(
+        "FROST preprocessing: generating {} presignature(s), session {}",
+        count, session_id
+    );
+
+    let result: Result<Vec<PresignatureId>, String> = async {
+        let key_share: FrostKeyShare = load_key_share(key_share_data, FrostCiphersuite::Bitcoin)?;
+        if parties_at_keygen.get(usize::from(party_index)) != Some(&key_share.i) {
+            return Err(format!(
+                "Party index {} does not match key share index {} in signers {:?}",
+                party_index, key_share.i, parties_at_keygen
+            ));
+        }
+        let others: Vec<u16> = (0..parties_at_keygen.len() as u16)
+            .filter(|&j| j != party_index)
+            .collect();
+        let mut transport = RoundTransport::new(session_id, party_index, incoming_rx, outgoing_tx);
+
+        let (nonces, own): (Vec<_>, Vec<_>) = (0..count).map(|_| commit(&key_share)).unzip();
+        transport.broadcast(1, &own).await?;
+
+        let mut received = transport
+            .collect::<Vec<PublicCommitments>>(1, &others)
+            .await?;
+        received.insert(party_index, own);
+        for (j, batch) in &received {
+            if batch.len() != count {
+                return Err(format!(
+                    "Party {} published {} commitments, expected {}",
+                    j,
+                    batch.len(),
+                    count
+                ));
+            }
+        }
+
+        nonces
+            .into_iter()
+            .enumerate()
+            .map(|(k, nonces)| {
+                let mut commitments: Vec<(u16, PublicCommitments)> = received
+                    .iter()
+                    .map(|(&j, batch)| (parties_at_keygen[usize::from(j)], batch[k].clone()))
+                    .collect();
+                commitments.sort_by_key(|&(signer, _)| signer);
+                store.insert(session_id, nonces, commitments)
+            })
+            .collect()
+    }
+    .await;
+
+    match result {
+        Ok(presignature_ids) => {
+            info!(
+                "FROST preprocessing completed in {:.2}s ({} available)",
+                start.elapsed().as_secs_f64(),
+                store.available()
+            );
+            FrostPreprocessingResult {
+                success: true,
+                presignature_ids,
+                error: None,
+                duration_secs: start.elapsed().as_secs_f64(),
+            }
+        }
+        Err(e) => {
+            error!("FROST preprocessing failed: {}", e);
+            FrostPreprocessingResult {
+                success: false,
+                presignature_ids: Vec::new(),
+                error: Some(e),
+                duration_secs: start.elapsed().as_secs_f64(),
+            }
+        }
+    }
+}
+
+/// Run one-round FROST signing using a stored presignature.
+///
+/// The presignature is removed from `store`, and from its file for a
+/// persistent store, before anything else happens, so it can never be used
+/// twice, even if signing fails.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_frost_presigned_signing(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    presignature_id: PresignatureId,
+    message_hash: &[u8; 32],
+    key_share_data: &[u8],
+    options: &SigningOptions,
+    store: &mut PresignatureStore,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostSigningResult {
+    let start = std::time::Instant::now();
+    let mut recorder = BenchmarkRecorder::new("FROST-Presigned-Signing", party_index, session_id);
+
+    info!("========================================");
+    info!("  FROST PRESIGNED SIGNING STARTING");
+    info!("========================================");
+    info!("Party index: {}", party_index);
+    info!("Presignature ID: {}", hex::encode(presignature_id));
+    info!("Message hash: {}", hex::encode(message_hash));
+
+    let presignature = store.take(&presignature_id);
+    let mut blamed_parties = Vec::new();
+    let result: Result<SigningTranscript, String> = async {
+        let Presignature {
+            nonces,
+            commitments,
+        } = presignature?.ok_or_else(|| {
+            format!(
+                "Presignature {} is unknown or already used",
+                hex::encode(presignature_id)
+            )
+        })?;
+
+        let step_start = std::time::Instant::now();
+        let key_share: FrostKeyShare = load_key_share(key_share_data, FrostCiphersuite::Bitcoin)?;
+        if parties_at_keygen.get(usize::from(party_index)) != Some(&key_share.i) {
+            return Err(format!(
+                "Party index {} does not match key share index {} in signers {:?}",
+                party_index, key_share.i, parties_at_keygen
+            ));
+        }
+        let output_key = verifying_key(key_share.as_ref(), options)?;
+        let tweak = signing_tweak(key_share.as_ref(), options.mode, options.taproot_tweak)?;
+        recorder.record_step("1. Deserialize key_share", step_start.elapsed());
+
+        let step_start = std::time::Instant::now();
+        let mut signers = parties_at_keygen.to_vec();
+        signers.sort_unstable();
+        if !commitments.iter().map(|(signer, _)| *signer).eq(signers) {
+            return Err(format!(
+                "Presignature {} was not agreed with signers {:?}",
+                hex::encode(presignature_id),
+                parties_at_keygen
+            ));
+        }
+        let partial = sign_share(
+            &key_share,
+            nonces,
+            message_hash,
+            &commitments,
+            tweak,
+            options.nonce_ledger.as_ref(),
+        )
+        .await?;
+        recorder.record_step("2. Compute partial signature", step_start.elapsed());
+
+        // Single online round: exchange partial signatures
+        let step_start = std::time::Instant::now();
+        let others: Vec<u16> = (0..parties_at_keygen.len() as u16)
+            .filter(|&j| j != party_index)
+            .collect();
+        let mut transport = RoundTransport::new(session_id, party_index, incoming_rx, outgoing_tx);
+        transport.broadcast(2, &partial).await?;
+        let mut partials = transport.collect::<PartialSignature>(2, &others).await?;
+        partials.insert(party_index, partial);
+        recorder.record_step("3. Online round (partial signatures)", step_start.elapsed());
+
+        let step_start = std::time::Instant::now();
+        let partials: BTreeMap<u16, PartialSignature> = partials
+            .into_iter()
+            .map(|(j, partial)| (parties_at_keygen[usize::from(j)], partial))
+            .collect();
+        let shares: Vec<_> = commitments
+            .into_iter()
+            .map(|(signer, c)| {
+                let partial = partials[&signer].clone();
+                (signer, c, partial)
+            })
+            .collect();
+        let signature = aggregate_with_blame(
+            key_share.as_ref(),
+            &shares,
+            message_hash,
+            tweak,
+            &output_key,
+        )
+        .map_err(|(e, blamed)| {
+            blamed_parties = blamed;
+            e
+        })?;
+        recorder.record_step("4. Aggregate signature", step_start.elapsed());
+
+        Ok(SigningTranscript::new(
+            session_id,
+            parties_at_keygen,
+            message_hash,
+            key_share.as_ref(),
+            options,
+            signature,
+            output_key,
+        ))
+    }
+    .await;
+
+    let benchmark = if options.enable_benchmark {
+        recorder.complete();
+        let report = recorder.report();
+        report.log();
+        Some(report)
+    } else {
+        None
+    };
+
+    match result {
+        Ok(transcript) => {
+            info!(
+                "FROST presigned signing completed in {:.2}s",
+                start.elapsed().as_secs_f64()
+            );
+            FrostSigningResult {
+                success: true,
+                signature: Some(transcript.signature),
+                output_key: Some(transcript.output_key),
+                error: None,
+                duration_secs: start.elapsed().as_secs_f64(),
+                benchmark,
+                blamed_parties,
+                transcript: Some(transcript),
+            }
+        }
+        Err(e) => {
+            error!("FROST presigned signing failed: {}", e);
+            FrostSigningResult {
+                success: false,
+                signature: None,
+                output_key: None,
+                error: Some(e),
+                duration_secs: start.elapsed().as_secs_f64(),
+                benchmark,
+                blamed_parties,
+                transcript: None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::super::local;
+    use super::*;
+
+    #[test]
+    fn test_preprocess_then_sign() {
+        let key_shares = local::key_shares(2, 3);
+        let signers = [0u16, 2];
+        let key_share = |i: u16| &key_shares[usize::from(signers[usize::from(i)])];
+        let route = |m: &ProtocolMessage| (m.sender, m.recipient);
+        let stores: Vec<Mutex<PresignatureStore>> =
+            signers.iter().map(|_| Mutex::default()).collect();
+        // One party already holds a presignature from an earlier session
+        local::run_parties(1, route, |_, rx, tx| {
+            let stores = &stores;
+            async move {
+                let mut store = stores[1].lock().unwrap();
+                run_frost_preprocessing(0, &[2], "earlier", key_share(1), 1, &mut store, rx, tx)
+                    .await
+            }
+        });
+
+        let preprocessed = local::run_parties(2, route, |i, rx, tx| {
+            let stores = &stores;
+            async move {
+                let mut store = stores[usize::from(i)].lock().unwrap();
+                run_frost_preprocessing(i, &signers, "pre", key_share(i), 2, &mut store, rx, tx)
+                    .await
+            }
+        });
+        for result in &preprocessed {
+            assert!(result.success, "preprocessing failed: {:?}", result.error);
+            assert_eq!(result.presignature_ids, preprocessed[0].presignature_ids);
+        }
+        let id = preprocessed[0].presignature_ids[0];
+
+        let message_hash = [0x42u8; 32];
+        let sign = |session_id: &'static str| {
+            local::run_parties(2, route, |i, rx, tx| {
+                let stores = &stores;
+                async move {
+                    let mut store = stores[usize::from(i)].lock().unwrap();
+                    run_frost_presigned_signing(
+                        i,
+                        &signers,
+                        session_id,
+                        id,
+                        &message_hash,
+                        key_share(i),
+                        &SigningOptions::default(),
+                        &mut store,
+                        rx,
+                        tx,
+                    )
+                    .await
+                }
+            })
+        };
+        for result in sign("sign") {
+            assert!(result.success, "signing failed: {:?}", result.error);
+            let output_key = result.output_key.unwrap();
+            assert!(result
+                .signature
+                .unwrap()
+                .verify(&output_key, &message_hash)
+                .is_ok());
+        }
+        assert_eq!(stores[0].lock().unwrap().available(), 1);
+        assert_eq!(stores[1].lock().unwrap().available(), 2);
+
+        // The presignature is gone after one use
+        for result in sign("sign-again") {
+            assert!(!result.success);
+        }
+    }
+
+    #[test]
+    fn test_presignatures_survive_restart() {
+        let key_shares = local::key_shares(2, 3);
+        let signers = [0u16, 2];
+        let key_share = |i: u16| &key_shares[usize::from(signers[usize::from(i)])];
+        let route = |m: &ProtocolMessage| (m.sender, m.recipient);
+        let paths: Vec<PathBuf> = signers
+            .iter()
+            .map(|j| {
+                std::env::temp_dir().join(format!(
+                    "frost-presignatures-{}-{}-{}.json",
+                    std::process::id(),
+                    line!(),
+                    j
+                ))
+            })
+            .collect();
+        for path in &paths {
+            let _ = std::fs::remove_file(path);
+        }
+
+        let preprocessed = local::run_parties(2, route, |i, rx, tx| {
+            let path = &paths[usize::from(i)];
+            async move {
+                let mut store = PresignatureStore::open(path).unwrap();
+                run_frost_preprocessing(i, &signers, "pre", key_share(i), 1, &mut store, rx, tx)
+                    .await
+            }
+        });
+        for result in &preprocessed {
+            assert!(result.success, "preprocessing failed: {:?}", result.error);
+        }
+        let id = preprocessed[0].presignature_ids[0];
+
+        // Restart: every party reopens its store from disk
+        let message_hash = [0x42u8; 32];
+        let results = local::run_parties(2, route, |i, rx, tx| {
+            let path = &paths[usize::from(i)];
+            async move {
+                let mut store = PresignatureStore::open(path).unwrap();
+                assert_eq!(store.available_ids(), vec![id]);
+                run_frost_presigned_signing(
+                    i,
+                    &signers,
+                    "sign",
+                    id,
+                    &message_hash,
+                    key_share(i),
+                    &SigningOptions::default(),
+                    &mut store,
+                    rx,
+                    tx,
+                )
+                .await
+            }
+        });
+        for result in results {
+            assert!(result.success, "signing failed: {:?}", result.error);
+        }
+
+        // The used presignature is gone from disk as well
+        for path in &paths {
+            assert_eq!(PresignatureStore::open(path).unwrap().available(), 0);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
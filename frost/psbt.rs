@@ -0,0 +1,520 @@
+//! PSBT signing with FROST threshold keys.
+//!
+//! This module finds the Taproot inputs of a PSBT that a FROST group key can
+//! sign for, computes their BIP-341 sighashes, batch-signs them with FROST
+//! and writes the resulting signatures back into the PSBT.
+
+use async_channel::{Receiver, Sender};
+use bitcoin::hashes::Hash;
+use bitcoin::key::XOnlyPublicKey;
+use bitcoin::opcodes::all::OP_CODESEPARATOR;
+use bitcoin::psbt::{Input, Psbt};
+use bitcoin::script::Instruction;
+use bitcoin::sighash::TapSighashType;
+use bitcoin::taproot::TapLeafHash;
+use bitcoin::{TxIn, TxOut};
+use tracing::{error, info};
+
+use super::batch::run_frost_batch_signing;
+use super::ciphersuite::{load_key_share, FrostCiphersuite};
+use super::sighash::{taproot_sighash, TapLeafSpend};
+use super::signing::{
+    FrostKeyShare, ProtocolMessage, SchnorrSignature, SigningMode, SigningOptions,
+};
+use super::taproot::TaprootTweak;
+
+/// How a PSBT input is spent by the FROST key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaprootSpend {
+    /// Key-path spend with the tweaked output key
+    KeyPath {
+        /// Script-tree merkle root the output key commits to
+        merkle_root: Option<[u8; 32]>,
+    },
+    /// Script-path spend of a tapleaf containing the FROST key
+    ScriptPath {
+        /// Hash of the tapleaf being spent
+        leaf_hash: TapLeafHash,
+    },
+}
+
+/// A single signature the FROST key must produce for a PSBT.
+#[derive(Debug, Clone)]
+pub struct PsbtSigningRequest {
+    /// Index of the input in the unsigned transaction
+    pub input_index: usize,
+    /// BIP-341 sighash to sign
+    pub sighash: [u8; 32],
+    /// Sighash type committed to by the signature
+    pub sighash_type: TapSighashType,
+    /// Key-path or script-path spend
+    pub spend: TaprootSpend,
+}
+
+/// Result of signing a PSBT with FROST.
+#[derive(Debug)]
+pub struct FrostPsbtSigningResult {
+    pub success: bool,
+    /// Input indices that received a signature
+    pub signed_inputs: Vec<usize>,
+    /// Error message if failed
+    pub error: Option<String>,
+    /// Duration of all signing sessions
+    pub duration_secs: f64,
+}
+
+/// Check whether a tapscript pushes the given x-only key.
+fn script_contains_key(script: &bitcoin::Script, key_id: &[u8; 32]) -> bool {
+    script
+        .instructions()
+        .any(|ins| matches!(ins, Ok(Instruction::PushBytes(bytes)) if bytes.as_bytes() == key_id))
+}
+
+/// Check whether a tapscript contains `OP_CODESEPARATOR`.
+///
+/// The sighash of such a leaf depends on which separator was executed last,
+/// which cannot be known from the PSBT, so these leaves are not signed.
+fn script_has_codeseparator(script: &bitcoin::Script) -> bool {
+    script
+        .instructions()
+        .any(|ins| matches!(ins, Ok(Instruction::Op(op)) if op == OP_CODESEPARATOR))
+}
+
+/// Sighash type for a Taproot input, defaulting to `SIGHASH_DEFAULT`.
+fn input_sighash_type(input: &Input) -> Result<TapSighashType, String> {
+    match input.sighash_type {
+        Some(ty) => ty
+            .taproot_hash_ty()
+            .map_err(|e| format!("Invalid taproot sighash type: {}", e)),
+        None => Ok(TapSighashType::Default),
+    }
+}
+
+/// Output spent by a PSBT input, from `witness_utxo` or else `non_witness_utxo`.
+fn input_prevout(input_index: usize, input: &Input, txin: &TxIn) -> Result<TxOut, String> {
+    if let Some(utxo) = &input.witness_utxo {
+        return Ok(utxo.clone());
+    }
+    let prev_tx = input.non_witness_utxo.as_ref().ok_or_else(|| {
+        format!(
+            "Input {} has neither witness_utxo nor non_witness_utxo",
+            input_index
+        )
+    })?;
+    let outpoint = txin.previous_output;
+    if prev_tx.compute_txid() != outpoint.txid {
+        return Err(format!(
+            "Input {}: non_witness_utxo is not the previous transaction {}",
+            input_index, outpoint.txid
+        ));
+    }
+    prev_tx
+        .output
+        .get(outpoint.vout as usize)
+        .cloned()
+        .ok_or_else(|| {
+            format!(
+                "Input {}: previous transaction has no output {}",
+                input_index, outpoint.vout
+            )
+        })
+}
+
+/// Compute the signing requests for every PSBT input spendable by `key_id`.
+///
+/// `key_id` is the FROST group key (x-only, 32 bytes) as returned by keygen.
+/// Inputs whose internal key is `key_id` are signed on the key path; tapleaves
+/// that push `key_id` are signed on the script path. Tapleaves containing
+/// `OP_CODESEPARATOR` are rejected.
+pub fn psbt_signing_requests(
+    psbt: &Psbt,
+    key_id: &[u8; 32],
+) -> Result<Vec<PsbtSigningRequest>, String> {
+    let tx = &psbt.unsigned_tx;
+    let prevouts: Vec<TxOut> = psbt
+        .inputs
+        .iter()
+        .zip(&tx.input)
+        .enumerate()
+        .map(|(i, (input, txin))| input_prevout(i, input, txin))
+        .collect::<Result<_, _>>()?;
+
+    let mut requests = Vec::new();
+    for (input_index, input) in psbt.inputs.iter().enumerate() {
+        let sighash_type = input_sighash_type(input)?;
+
+        let is_key_path = input
+            .tap_internal_key
+            .is_some_and(|key| key.serialize() == *key_id);
+        if is_key_path {
+            let sighash = taproot_sighash(tx, &prevouts, input_index, sighash_type, None, None)
+                .map_err(|e| format!("Input {}: {}", input_index, e))?;
+            requests.push(PsbtSigningRequest {
+                input_index,
+                sighash,
+                sighash_type,
+                spend: TaprootSpend::KeyPath {
+                    merkle_root: input.tap_merkle_root.map(|root| root.to_byte_array()),
+                },
+            });
+        }
+
+        for (script, leaf_version) in input.tap_scripts.values() {
+            if !script_contains_key(script, key_id) {
+                continue;
+            }
+            if script_has_codeseparator(script) {
+                return Err(format!(
+                    "Input {}: leaf script contains OP_CODESEPARATOR, which is not supported",
+                    input_index
+                ));
+            }
+            let leaf_hash = TapLeafHash::from_script(script, *leaf_version);
+            let sighash = taproot_sighash(
+                tx,
+                &prevouts,
+                input_index,
+                sighash_type,
+                None,
+                Some(TapLeafSpend::new(leaf_hash)),
+            )
+            .map_err(|e| format!("Input {}: {}", input_index, e))?;
+            requests.push(PsbtSigningRequest {
+                input_index,
+                sighash,
+                sighash_type,
+                spend: TaprootSpend::ScriptPath { leaf_hash },
+            });
+        }
+    }
+
+    Ok(requests)
+}
+
+/// Write a FROST signature for `request` into the PSBT.
+pub fn apply_psbt_signature(
+    psbt: &mut Psbt,
+    key_id: &[u8; 32],
+    request: &PsbtSigningRequest,
+    signature: &SchnorrSignature,
+) -> Result<(), String> {
+    let input = psbt
+        .inputs
+        .get_mut(request.input_index)
+        .ok_or_else(|| format!("PSBT has no input {}", request.input_index))?;
+    let tap_sig = signature.to_taproot_signature(request.sighash_type);
+
+    match request.spend {
+        TaprootSpend::KeyPath { .. } => {
+            input.tap_key_sig = Some(tap_sig);
+        }
+        TaprootSpend::ScriptPath { leaf_hash } => {
+            let key =
+                XOnlyPublicKey::from_slice(key_id).map_err(|e| format!("Invalid key ID: {}", e))?;
+            input.tap_script_sigs.insert((key, leaf_hash), tap_sig);
+        }
+    }
+    Ok(())
+}
+
+/// Sign every Taproot input of a PSBT that `key_id` can spend.
+///
+/// Signatures made under the same key (key-path spends with the same merkle
+/// root, or script-path spends) are produced in one batch session, with
+/// session ID `{session_id}:{n}` for the n-th batch. `open_session` must
+/// return the relay channels for a given session ID, so that messages of
+/// consecutive sessions are never mixed. The PSBT is only updated once every
+/// signature has been produced, so a failed run leaves it untouched.
+#[allow(clippy::too_many_arguments)]
+pub async fn sign_psbt_with_frost<F>(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    psbt: &mut Psbt,
+    key_id: &[u8; 32],
+    key_share_data: &[u8],
+    mut open_session: F,
+) -> FrostPsbtSigningResult
+where
+    F: FnMut(&str) -> (Receiver<ProtocolMessage>, Sender<ProtocolMessage>),
+{
+    let start = std::time::Instant::now();
+
+    let fail = |error: String| FrostPsbtSigningResult {
+        success: false,
+        signed_inputs: Vec::new(),
+        error: Some(error),
+        duration_secs: start.elapsed().as_secs_f64(),
+    };
+
+    // Make sure the key share actually belongs to the requested key
+    let key_share: FrostKeyShare = match load_key_share(key_share_data, FrostCiphersuite::Bitcoin) {
+        Ok(ks) => ks,
+        Err(e) => {
+            error!("Failed to load key share: {}", e);
+            return fail(e);
+        }
+    };
+    let pubkey_bytes = key_share.shared_public_key().to_bytes(true);
+    if pubkey_bytes[1..] != key_id[..] {
+        error!("Key share does not match key ID {}", hex::encode(key_id));
+        return fail(format!(
+            "Key share does not match key ID {}",
+            hex::encode(key_id)
+        ));
+    }
+
+    let requests = match psbt_signing_requests(psbt, key_id) {
+        Ok(requests) => requests,
+        Err(e) => {
+            error!("Failed to compute PSBT sighashes: {}", e);
+            return fail(e);
+        }
+    };
+    info!(
+        "PSBT has {} signature(s) to produce for key {}",
+        requests.len(),
+        hex::encode(key_id)
+    );
+
+    // Group the requests by the key they are signed under
+    let mut batches: Vec<(SigningOptions, Vec<&PsbtSigningRequest>)> = Vec::new();
+    for request in &requests {
+        let (mode, taproot_tweak) = match request.spend {
+            TaprootSpend::KeyPath { merkle_root } => (
+                SigningMode::KeyPath,
+                TaprootTweak::from_merkle_root(merkle_root),
+            ),
+            TaprootSpend::ScriptPath { .. } => (SigningMode::ScriptPath, TaprootTweak::KeyOnly),
+        };
+        match batches
+            .iter_mut()
+            .find(|(options, _)| options.mode == mode && options.taproot_tweak == taproot_tweak)
+        {
+            Some((_, batch)) => batch.push(request),
+            None => batches.push((
+                SigningOptions {
+                    mode,
+                    taproot_tweak,
+                    verify_signature: true,
+                    ..Default::default()
+                },
+                vec![request],
+            )),
+        }
+    }
+
+    let mut signed_psbt = psbt.clone();
+    let mut signed_inputs = Vec::new();
+    for (n, (options, batch)) in batches.iter().enumerate() {
+        let inputs: Vec<usize> = batch.iter().map(|request| request.input_index).collect();
+        let sighashes: Vec<[u8; 32]> = batch.iter().map(|request| request.sighash).collect();
+
+        let batch_session_id = format!("{}:{}", session_id, n);
+        let (incoming_rx, outgoing_tx) = open_session(&batch_session_id);
+        let result = run_frost_batch_signing(
+            party_index,
+            parties_at_keygen,
+            &batch_session_id,
+            &sighashes,
+            key_share_data,
+            options,
+            incoming_rx,
+            outgoing_tx,
+        )
+        .await;
+
+        if !result.success || result.signatures.len() != batch.len() {
+            let e = result
+                .error
+                .unwrap_or_else(|| "Signing produced no signature".to_string());
+            error!("Inputs {:?}: FROST signing failed: {}", inputs, e);
+            return fail(format!("Inputs {:?}: {}", inputs, e));
+        }
+
+        for (request, signature) in batch.iter().zip(&result.signatures) {
+            if let Err(e) = apply_psbt_signature(&mut signed_psbt, key_id, request, signature) {
+                return fail(e);
+            }
+        }
+        signed_inputs.extend(inputs);
+    }
+    signed_inputs.sort_unstable();
+    signed_inputs.dedup();
+
+    *psbt = signed_psbt;
+    info!(
+        "Signed {} PSBT input(s) in {:.2}s",
+        signed_inputs.len(),
+        start.elapsed().as_secs_f64()
+    );
+
+    FrostPsbtSigningResult {
+        success: true,
+        signed_inputs,
+        error: None,
+        duration_secs: start.elapsed().as_secs_f64(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::key::Secp256k1;
+    use bitcoin::opcodes::all::OP_CHECKSIG;
+    use bitcoin::script::Builder;
+    use bitcoin::taproot::{LeafVersion, TaprootBuilder};
+    use bitcoin::transaction::Version;
+    use bitcoin::{Amount, OutPoint, ScriptBuf, Sequence, Transaction, Witness};
+
+    fn transaction(inputs: Vec<OutPoint>, outputs: Vec<TxOut>) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .into_iter()
+                .map(|previous_output| TxIn {
+                    previous_output,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::MAX,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: outputs,
+        }
+    }
+
+    #[test]
+    fn test_script_contains_key() {
+        let key = [7u8; 32];
+        let script = Builder::new()
+            .push_slice(key)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+
+        assert!(script_contains_key(&script, &key));
+        assert!(!script_contains_key(&script, &[8u8; 32]));
+        assert!(!script_has_codeseparator(&script));
+
+        let script = Builder::new()
+            .push_opcode(OP_CODESEPARATOR)
+            .push_slice(key)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        assert!(script_has_codeseparator(&script));
+    }
+
+    #[test]
+    fn test_psbt_signing_requests_and_apply_signature() {
+        let secp = Secp256k1::verification_only();
+        let key_id: [u8; 32] =
+            hex::decode("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let key = XOnlyPublicKey::from_slice(&key_id).unwrap();
+        let other_key = XOnlyPublicKey::from_slice(
+            &hex::decode("f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9")
+                .unwrap(),
+        )
+        .unwrap();
+
+        // Input 0: key-path output of `key`, described by witness_utxo
+        let key_path_utxo = TxOut {
+            value: Amount::from_sat(50_000),
+            script_pubkey: ScriptBuf::new_p2tr(&secp, key, None),
+        };
+        // Input 1: `<key> OP_CHECKSIG` leaf under another internal key,
+        // described only by the full previous transaction
+        let leaf = Builder::new()
+            .push_slice(key_id)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(0, leaf.clone())
+            .unwrap()
+            .finalize(&secp, other_key)
+            .unwrap();
+        let script_path_utxo = TxOut {
+            value: Amount::from_sat(70_000),
+            script_pubkey: ScriptBuf::new_p2tr(&secp, other_key, spend_info.merkle_root()),
+        };
+        let prev_tx = transaction(
+            vec![OutPoint::null()],
+            vec![key_path_utxo.clone(), script_path_utxo.clone()],
+        );
+
+        let tx = transaction(
+            vec![
+                OutPoint::new(prev_tx.compute_txid(), 0),
+                OutPoint::new(prev_tx.compute_txid(), 1),
+            ],
+            vec![TxOut {
+                value: Amount::from_sat(110_000),
+                script_pubkey: ScriptBuf::new_p2tr(&secp, other_key, None),
+            }],
+        );
+        let mut psbt = Psbt::from_unsigned_tx(tx.clone()).unwrap();
+        psbt.inputs[0].witness_utxo = Some(key_path_utxo.clone());
+        psbt.inputs[0].tap_internal_key = Some(key);
+        psbt.inputs[1].non_witness_utxo = Some(prev_tx.clone());
+        psbt.inputs[1].tap_internal_key = Some(other_key);
+        let control_block = spend_info
+            .control_block(&(leaf.clone(), LeafVersion::TapScript))
+            .unwrap();
+        psbt.inputs[1]
+            .tap_scripts
+            .insert(control_block, (leaf.clone(), LeafVersion::TapScript));
+
+        let requests = psbt_signing_requests(&psbt, &key_id).unwrap();
+        assert_eq!(requests.len(), 2);
+
+        let prevouts = [key_path_utxo, script_path_utxo];
+        let leaf_hash = TapLeafHash::from_script(&leaf, LeafVersion::TapScript);
+        assert_eq!(requests[0].input_index, 0);
+        assert_eq!(
+            requests[0].spend,
+            TaprootSpend::KeyPath { merkle_root: None }
+        );
+        assert_eq!(
+            requests[0].sighash,
+            taproot_sighash(&tx, &prevouts, 0, TapSighashType::Default, None, None).unwrap()
+        );
+        assert_eq!(requests[1].input_index, 1);
+        assert_eq!(requests[1].spend, TaprootSpend::ScriptPath { leaf_hash });
+        assert_eq!(
+            requests[1].sighash,
+            taproot_sighash(
+                &tx,
+                &prevouts,
+                1,
+                TapSighashType::Default,
+                None,
+                Some(TapLeafSpend::new(leaf_hash)),
+            )
+            .unwrap()
+        );
+
+        // BIP-340 test vector 0 as a stand-in signature
+        let signature: SchnorrSignature =
+            "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca8215\
+             25f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0"
+                .parse()
+                .unwrap();
+        for request in &requests {
+            apply_psbt_signature(&mut psbt, &key_id, request, &signature).unwrap();
+        }
+        let expected = signature.to_taproot_signature(TapSighashType::Default);
+        assert_eq!(psbt.inputs[0].tap_key_sig, Some(expected));
+        assert_eq!(
+            psbt.inputs[1].tap_script_sigs.get(&(key, leaf_hash)),
+            Some(&expected)
+        );
+        assert!(psbt.inputs[1].tap_key_sig.is_none());
+
+        // A non_witness_utxo that is not the spent transaction is rejected
+        psbt.inputs[1].non_witness_utxo = Some(transaction(vec![], vec![]));
+        assert!(psbt_signing_requests(&psbt, &key_id).is_err());
+    }
+}
@@ -0,0 +1,627 @@
+//! Round-by-round FROST signing primitives.
+//!
+//! The batch, preprocessing and coordinator flows are built from the
+//! individual rounds rather than Givre's full-signing protocol, which keeps
+//! nonces internal and only takes a merkle root as Taproot tweak. Interactive
+//! signing of one sighash runs the full-signing protocol (see
+//! [`super::signing`]). This module wraps Givre's `round1`/`round2`/
+//! `aggregate` API and provides a small broadcast-and-collect transport over
+//! our relay channels.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::Duration;
+
+use async_channel::{Receiver, Sender};
+use async_io::Timer;
+use futures::future::{self, Either};
+use generic_ec::{Point, Scalar};
+use rand::rngs::OsRng;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use givre::ciphersuite::{Bitcoin, Ciphersuite};
+
+use super::nonce_ledger::NonceLedgerHandle;
+use super::signing::{FrostKeyShare, ProtocolMessage, SchnorrSignature, SigningMode};
+use super::taproot::TaprootTweak;
+use super::timing::TransportLog;
+
+/// Curve of the Bitcoin ciphersuite.
+pub type FrostCurve = <Bitcoin as Ciphersuite>::Curve;
+
+/// Secret single-use nonces generated in round 1.
+pub type SecretNonces = givre::signing::round1::SecretNonces<FrostCurve>;
+
+/// Public nonce commitments broadcast in round 1.
+pub type PublicCommitments = givre::signing::round1::PublicCommitments<FrostCurve>;
+
+/// Partial signature produced in round 2.
+pub type PartialSignature = givre::signing::round2::SigShare<FrostCurve>;
+
+/// Public part of a key share: group key and every signer's verification share.
+pub type FrostKeyInfo = givre::key_share::KeyInfo<FrostCurve>;
+
+/// Generate fresh nonces and their public commitments.
+pub fn commit(key_share: &FrostKeyShare) -> (SecretNonces, PublicCommitments) {
+    givre::signing::round1::commit::<Bitcoin>(&mut OsRng, key_share)
+}
+
+/// Compressed group public key of `key_info` (33 bytes).
+pub fn group_public_key(key_info: &FrostKeyInfo) -> Vec<u8> {
+    key_info.shared_public_key.to_bytes(true).to_vec()
+}
+
+/// BIP-341 tweak scalar for the given signing mode, if any.
+pub fn signing_tweak(
+    key_info: &FrostKeyInfo,
+    mode: SigningMode,
+    tweak: TaprootTweak,
+) -> Result<Option<Scalar<FrostCurve>>, String> {
+    if !mode.is_tweaked() {
+        return Ok(None);
+    }
+    let pubkey_bytes = group_public_key(key_info);
+    let t = tweak.scalar(&pubkey_bytes[1..])?;
+    Scalar::from_be_bytes(t)
+        .map(Some)
+        .map_err(|_| "Taproot tweak is not a valid scalar".to_string())
+}
+
+/// SHA-256 fingerprint of a signer's nonce commitments, as recorded in the
+/// nonce ledger.
+pub fn commitment_fingerprint(commitments: &PublicCommitments) -> Result<[u8; 32], String> {
+    let bytes = serde_json::to_vec(commitments)
+        .map_err(|e| format!("Failed to serialize commitments: {}", e))?;
+    Ok(Sha256::digest(bytes).into())
+}
+
+/// Produce this signer's partial signature over `msg`.
+///
+/// `commitments` holds every signer's round-1 commitments keyed by their
+/// index at keygen. If a `ledger` is given, this signer's nonce is durably
+/// recorded as consumed before the partial signature is computed, and
+/// signing is refused if it was consumed before.
+pub async fn sign_share(
+    key_share: &FrostKeyShare,
+    nonces: SecretNonces,
+    msg: &[u8],
+    commitments: &[(u16, PublicCommitments)],
+    tweak: Option<Scalar<FrostCurve>>,
+    ledger: Option<&NonceLedgerHandle>,
+) -> Result<PartialSignature, String> {
+    if let Some(ledger) = ledger {
+        let own = commitments
+            .iter()
+            .find(|(signer, _)| *signer == key_share.i)
+            .map(|(_, c)| c)
+            .ok_or_else(|| format!("No commitments for own index {}", key_share.i))?;
+        let key_id = key_share.shared_public_key().to_bytes(true).to_vec();
+        ledger
+            .consume(&key_id, key_share.i, &commitment_fingerprint(own)?)
+            .await?;
+    }
+
+    givre::signing::round2::sign_with_tweak::<Bitcoin>(key_share, tweak, nonces, msg, commitments)
+        .map_err(|e| format!("Failed to produce partial signature: {:?}", e))
+}
+
+/// Aggregate partial signatures into a BIP-340 signature.
+pub fn aggregate(
+    key_info: &FrostKeyInfo,
+    shares: &[(u16, PublicCommitments, PartialSignature)],
+    msg: &[u8],
+    tweak: Option<Scalar<FrostCurve>>,
+) -> Result<SchnorrSignature, String> {
+    let signature =
+        givre::signing::aggregate::aggregate_with_tweak::<Bitcoin>(key_info, tweak, shares, msg)
+            .map_err(|e| format!("Failed to aggregate signature: {:?}", e))?;
+    schnorr_signature(&signature)
+}
+
+/// Encode a Givre signature as a BIP-340 signature.
+pub(crate) fn schnorr_signature(
+    signature: &givre::signing::aggregate::Signature<Bitcoin>,
+) -> Result<SchnorrSignature, String> {
+    let r_point_bytes: Vec<u8> = signature.r.to_bytes().into();
+    let r = match r_point_bytes.len() {
+        33 => &r_point_bytes[1..],
+        32 => &r_point_bytes[..],
+        len => return Err(format!("Unexpected R point length: {}", len)),
+    };
+    let z_bytes = signature.z.to_be_bytes();
+    let mut sig = [0u8; 64];
+    sig[..32].copy_from_slice(r);
+    sig[32..].copy_from_slice(z_bytes.as_ref());
+    SchnorrSignature::try_from(&sig[..])
+}
+
+/// Whether a secp256k1 point has an odd y coordinate (BIP-340 normalization).
+fn has_odd_y(point: &Point<FrostCurve>) -> bool {
+    point.to_bytes(true)[0] == 0x03
+}
+
+/// Verify one signer's partial signature against its verification share.
+///
+/// Follows RFC 9591 `verify_signature_share`, with the BIP-340 adjustments
+/// the Bitcoin ciphersuite applies: the (tweaked) group key and the group
+/// commitment are normalized to even y, which flips the sign of the
+/// corresponding share terms.
+pub fn verify_share(
+    key_info: &FrostKeyInfo,
+    signer: u16,
+    commitments: &[(u16, PublicCommitments)],
+    share: &PartialSignature,
+    msg: &[u8],
+    tweak: Option<Scalar<FrostCurve>>,
+) -> Result<(), String> {
+    let generator = Point::<FrostCurve>::generator().to_point();
+    let preimage = |j: u16| -> Result<Scalar<FrostCurve>, String> {
+        key_info
+            .share_preimage(j)
+            .map(Scalar::from)
+            .ok_or_else(|| format!("Unknown signer index {}", j))
+    };
+
+    // Group key as used for signing: normalized, tweaked, normalized again
+    let mut group_key = Point::from(key_info.shared_public_key);
+    let mut verification_share = Point::from(
+        *key_info
+            .public_shares
+            .get(usize::from(signer))
+            .ok_or_else(|| format!("No verification share for signer {}", signer))?,
+    );
+    if has_odd_y(&group_key) {
+        group_key = -group_key;
+        verification_share = -verification_share;
+    }
+    if let Some(t) = tweak {
+        group_key = group_key + generator * t;
+        verification_share = verification_share + generator * t;
+        if has_odd_y(&group_key) {
+            group_key = -group_key;
+            verification_share = -verification_share;
+        }
+    }
+
+    // Binding factors over the encoded commitment list
+    let mut encoded_commitments = Vec::new();
+    for (j, c) in commitments {
+        encoded_commitments.extend_from_slice(Bitcoin::serialize_scalar(&preimage(*j)?).as_ref());
+        encoded_commitments.extend_from_slice(Bitcoin::serialize_point(&c.hiding_comm).as_ref());
+        encoded_commitments.extend_from_slice(Bitcoin::serialize_point(&c.binding_comm).as_ref());
+    }
+    let msg_hash = Bitcoin::h4().chain_update(msg).finalize();
+    let commitments_hash = Bitcoin::h5().chain_update(&encoded_commitments).finalize();
+    let group_key_bytes = Bitcoin::serialize_point(&group_key);
+
+    let mut group_commitment = Point::<FrostCurve>::zero();
+    let mut own_commitment = None;
+    for (j, c) in commitments {
+        let binding_factor = Bitcoin::h1(&[
+            group_key_bytes.as_ref(),
+            msg_hash.as_ref(),
+            commitments_hash.as_ref(),
+            Bitcoin::serialize_scalar(&preimage(*j)?).as_ref(),
+        ]);
+        let comm = Point::from(c.hiding_comm) + Point::from(c.binding_comm) * binding_factor;
+        group_commitment = group_commitment + comm;
+        if *j == signer {
+            own_commitment = Some(comm);
+        }
+    }
+    let mut own_commitment =
+        own_commitment.ok_or_else(|| format!("Signer {} has no commitment", signer))?;
+    if has_odd_y(&group_commitment) {
+        group_commitment = -group_commitment;
+        own_commitment = -own_commitment;
+    }
+
+    // Lagrange coefficient of this signer among the signing set
+    let x_i = preimage(signer)?;
+    let mut lambda = Scalar::<FrostCurve>::one();
+    for (j, _) in commitments.iter().filter(|(j, _)| *j != signer) {
+        let x_j = preimage(*j)?;
+        let denominator = (x_j - x_i)
+            .invert()
+            .ok_or_else(|| format!("Duplicate signer index {}", j))?;
+        lambda = lambda * x_j * denominator;
+    }
+
+    let challenge = Bitcoin::compute_challenge(
+        &Bitcoin::normalize_point(group_commitment),
+        &Bitcoin::normalize_point(group_key),
+        msg,
+    );
+
+    let lhs = generator * share.0;
+    let rhs = own_commitment + verification_share * (challenge * lambda);
+    if lhs != rhs {
+        return Err(format!("Invalid partial signature from signer {}", signer));
+    }
+    Ok(())
+}
+
+/// Signers whose partial signatures failed verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidShares {
+    /// Index at keygen of every blamed signer, in ascending order
+    pub parties: Vec<u16>,
+}
+
+impl std::fmt::Display for InvalidShares {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid partial signatures from parties {:?}",
+            self.parties
+        )
+    }
+}
+
+/// Verify every partial signature and blame the signers of invalid ones.
+///
+/// `shares` holds `(index at keygen, commitments, partial signature)` for the
+/// whole signing set, as passed to [`aggregate`].
+pub fn verify_shares(
+    key_info: &FrostKeyInfo,
+    shares: &[(u16, PublicCommitments, PartialSignature)],
+    msg: &[u8],
+    tweak: Option<Scalar<FrostCurve>>,
+) -> Result<(), InvalidShares> {
+    let commitments: Vec<(u16, PublicCommitments)> =
+        shares.iter().map(|(j, c, _)| (*j, c.clone())).collect();
+    let mut parties: Vec<u16> = shares
+        .iter()
+        .filter(|(j, _, partial)| {
+            verify_share(key_info, *j, &commitments, partial, msg, tweak).is_err()
+        })
+        .map(|(j, _, _)| *j)
+        .collect();
+    parties.sort_unstable();
+
+    if parties.is_empty() {
+        Ok(())
+    } else {
+        Err(InvalidShares { parties })
+    }
+}
+
+/// Aggregate partial signatures, blaming invalid shares if the result does
+/// not verify against `output_key`.
+///
+/// Shares are only checked individually when aggregation fails, so the
+/// happy path costs a single signature verification.
+pub fn aggregate_with_blame(
+    key_info: &FrostKeyInfo,
+    shares: &[(u16, PublicCommitments, PartialSignature)],
+    msg: &[u8; 32],
+    tweak: Option<Scalar<FrostCurve>>,
+    output_key: &[u8; 32],
+) -> Result<SchnorrSignature, (String, Vec<u16>)> {
+    let aggregated = aggregate(key_info, shares, msg, tweak)
+        .and_then(|sig| sig.verify(output_key, msg).map(|_| sig));
+    match aggregated {
+        Ok(signature) => Ok(signature),
+        Err(e) => match verify_shares(key_info, shares, msg, tweak) {
+            Err(invalid) => Err((invalid.to_string(), invalid.parties)),
+            Ok(()) => Err((e, Vec::new())),
+        },
+    }
+}
+
+/// Run `fut` to completion, or return `None` if it takes longer than `timeout`.
+pub async fn with_timeout<F: Future>(fut: F, timeout: Duration) -> Option<F::Output> {
+    match future::select(Box::pin(fut), Timer::after(timeout)).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+/// Broadcast-and-collect transport for round-by-round protocols.
+///
+/// Messages for a later round that arrive early are buffered until that
+/// round is collected. Rounds are numbered from 1 on the wire and in
+/// transport timings.
+pub struct RoundTransport {
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+    session_id: String,
+    party_index: u16,
+    seq: u64,
+    pending: Vec<ProtocolMessage>,
+    timings: Option<TransportLog>,
+}
+
+impl RoundTransport {
+    pub fn new(
+        session_id: &str,
+        party_index: u16,
+        incoming_rx: Receiver<ProtocolMessage>,
+        outgoing_tx: Sender<ProtocolMessage>,
+    ) -> Self {
+        Self {
+            incoming_rx,
+            outgoing_tx,
+            session_id: session_id.to_string(),
+            party_index,
+            seq: 0,
+            pending: Vec::new(),
+            timings: None,
+        }
+    }
+
+    /// Timestamp every sent and received message into `timings`.
+    pub(crate) fn with_timings(mut self, timings: TransportLog) -> Self {
+        self.timings = Some(timings);
+        self
+    }
+
+    /// Send `msg` for `round` to one party, or to all parties if `recipient` is `None`.
+    pub async fn send<T: Serialize>(
+        &mut self,
+        recipient: Option<u16>,
+        round: u16,
+        msg: &T,
+    ) -> Result<(), String> {
+        self.seq += 1;
+        let payload =
+            serde_json::to_vec(msg).map_err(|e| format!("Failed to serialize message: {}", e))?;
+        self.outgoing_tx
+            .send(ProtocolMessage {
+                session_id: self.session_id.clone(),
+                sender: self.party_index,
+                recipient,
+                round,
+                payload,
+                seq: self.seq,
+            })
+            .await
+            .map_err(|e| format!("Failed to send message: {}", e))?;
+        if let Some(Ok(mut timings)) = self.timings.as_ref().map(|t| t.lock()) {
+            timings.sent(round);
+        }
+        Ok(())
+    }
+
+    /// Broadcast `msg` for `round` to all parties.
+    pub async fn broadcast<T: Serialize>(&mut self, round: u16, msg: &T) -> Result<(), String> {
+        self.send(None, round, msg).await
+    }
+
+    /// Wait for one `round` message from each party in `from`.
+    pub async fn collect<T: DeserializeOwned>(
+        &mut self,
+        round: u16,
+        from: &[u16],
+    ) -> Result<BTreeMap<u16, T>, String> {
+        let mut received = BTreeMap::new();
+
+        for msg in std::mem::take(&mut self.pending) {
+            self.dispatch(msg, round, from, &mut received)?;
+        }
+
+        while received.len() < from.len() {
+            let msg = self
+                .incoming_rx
+                .recv()
+                .await
+                .map_err(|_| format!("Channel closed while waiting for round {}", round))?;
+            self.log_received(&msg);
+            self.dispatch(msg, round, from, &mut received)?;
+        }
+        Ok(received)
+    }
+
+    /// Like [`collect`](Self::collect), but stop waiting after `timeout` and
+    /// return whatever arrived by then.
+    pub async fn collect_within<T: DeserializeOwned>(
+        &mut self,
+        round: u16,
+        from: &[u16],
+        timeout: Duration,
+    ) -> Result<BTreeMap<u16, T>, String> {
+        let mut received = BTreeMap::new();
+
+        for msg in std::mem::take(&mut self.pending) {
+            self.dispatch(msg, round, from, &mut received)?;
+        }
+
+        let mut expired = Timer::after(timeout);
+        while received.len() < from.len() {
+            let msg = match future::select(Box::pin(self.incoming_rx.recv()), &mut expired).await {
+                Either::Left((Ok(msg), _)) => msg,
+                Either::Left((Err(_), _)) => {
+                    return Err(format!("Channel closed while waiting for round {}", round))
+                }
+                Either::Right(_) => break,
+            };
+            self.log_received(&msg);
+            self.dispatch(msg, round, from, &mut received)?;
+        }
+        Ok(received)
+    }
+
+    fn log_received(&self, msg: &ProtocolMessage) {
+        if let Some(Ok(mut timings)) = self.timings.as_ref().map(|t| t.lock()) {
+            timings.received(msg.round, msg.sender);
+        }
+    }
+
+    /// Route a message received while collecting `round`.
+    ///
+    /// Messages for other sessions or addressed to another party are
+    /// dropped, messages for later rounds are kept for later and messages
+    /// from earlier rounds are reported and dropped.
+    fn dispatch<T: DeserializeOwned>(
+        &mut self,
+        msg: ProtocolMessage,
+        round: u16,
+        from: &[u16],
+        received: &mut BTreeMap<u16, T>,
+    ) -> Result<(), String> {
+        if msg.session_id != self.session_id || msg.recipient.is_some_and(|r| r != self.party_index)
+        {
+            return Ok(());
+        }
+        match msg.round.cmp(&round) {
+            Ordering::Greater => self.pending.push(msg),
+            Ordering::Less => warn!(
+                "Dropping stale round {} message from party {} in round {}",
+                msg.round, msg.sender, round
+            ),
+            Ordering::Equal => self.accept(msg, from, received)?,
+        }
+        Ok(())
+    }
+
+    fn accept<T: DeserializeOwned>(
+        &self,
+        msg: ProtocolMessage,
+        from: &[u16],
+        received: &mut BTreeMap<u16, T>,
+    ) -> Result<(), String> {
+        if !from.contains(&msg.sender) {
+            return Ok(());
+        }
+        let payload = serde_json::from_slice(&msg.payload).map_err(|e| {
+            format!(
+                "Failed to deserialize round {} message from party {}: {}",
+                msg.round, msg.sender, e
+            )
+        })?;
+        if received.insert(msg.sender, payload).is_some() {
+            return Err(format!(
+                "Party {} sent more than one round {} message",
+                msg.sender, msg.round
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ciphersuite::{load_key_share, FrostCiphersuite};
+    use super::super::local;
+    use super::*;
+
+    #[test]
+    fn test_round_transport_routes_by_round_and_recipient() {
+        let (peer_tx, incoming_rx) = async_channel::unbounded();
+        let (outgoing_tx, _outgoing_rx) = async_channel::unbounded();
+        let mut transport = RoundTransport::new("s", 0, incoming_rx, outgoing_tx);
+
+        let msg = |round: u16, recipient: Option<u16>, payload: u32| ProtocolMessage {
+            session_id: "s".to_string(),
+            sender: 1,
+            recipient,
+            round,
+            payload: serde_json::to_vec(&payload).unwrap(),
+            seq: u64::from(round),
+        };
+        // Round 3 arrives first, a round 1 message for another party is
+        // ignored and a late round 1 copy is dropped while collecting round 2
+        peer_tx.try_send(msg(1, Some(2), 99)).unwrap();
+        peer_tx.try_send(msg(3, None, 33)).unwrap();
+        peer_tx.try_send(msg(1, None, 11)).unwrap();
+        peer_tx.try_send(msg(1, None, 11)).unwrap();
+        peer_tx.try_send(msg(2, Some(0), 22)).unwrap();
+
+        futures::executor::block_on(async {
+            let round1 = transport.collect::<u32>(1, &[1]).await.unwrap();
+            assert_eq!(round1.get(&1), Some(&11));
+            let round2 = transport.collect::<u32>(2, &[1]).await.unwrap();
+            assert_eq!(round2.get(&1), Some(&22));
+            let round3 = transport.collect::<u32>(3, &[1]).await.unwrap();
+            assert_eq!(round3.get(&1), Some(&33));
+        });
+    }
+
+    #[test]
+    fn test_verify_share_accepts_honest_and_rejects_tampered_shares() {
+        let load = |shares: Vec<Vec<u8>>| -> Vec<FrostKeyShare> {
+            shares
+                .iter()
+                .map(|data| load_key_share(data, FrostCiphersuite::Bitcoin).unwrap())
+                .collect()
+        };
+        let is_odd =
+            |key_shares: &[FrostKeyShare]| group_public_key(key_shares[0].as_ref())[0] == 3;
+        // BIP-340 negates odd-y keys, so cover both parities of the group key
+        let mut key_sets: Vec<Vec<FrostKeyShare>> = Vec::new();
+        for _ in 0..32 {
+            let key_shares = load(local::key_shares(2, 3));
+            if key_sets
+                .iter()
+                .all(|set| is_odd(set) != is_odd(&key_shares))
+            {
+                key_sets.push(key_shares);
+            }
+            if key_sets.len() == 2 {
+                break;
+            }
+        }
+        assert_eq!(
+            key_sets.len(),
+            2,
+            "no group key of each parity in 32 keygens"
+        );
+
+        let msg = [0x42u8; 32];
+        let cases = [
+            (SigningMode::KeyPath, TaprootTweak::KeyOnly),
+            (SigningMode::KeyPath, TaprootTweak::MerkleRoot([7u8; 32])),
+            (SigningMode::ScriptPath, TaprootTweak::KeyOnly),
+        ];
+        for key_shares in &key_sets {
+            let signers = [&key_shares[0], &key_shares[2]];
+            for (mode, taproot_tweak) in cases {
+                let key_info: &FrostKeyInfo = signers[0].as_ref();
+                let tweak = signing_tweak(key_info, mode, taproot_tweak).unwrap();
+                let (nonces, commitments): (Vec<_>, Vec<_>) =
+                    signers.iter().map(|key_share| commit(key_share)).unzip();
+                let commitments: Vec<(u16, PublicCommitments)> = signers
+                    .iter()
+                    .map(|key_share| key_share.i)
+                    .zip(commitments)
+                    .collect();
+                let shares: Vec<_> = signers
+                    .iter()
+                    .zip(nonces)
+                    .zip(&commitments)
+                    .map(|((key_share, nonces), (i, c))| {
+                        let partial = futures::executor::block_on(sign_share(
+                            key_share,
+                            nonces,
+                            &msg,
+                            &commitments,
+                            tweak,
+                            None,
+                        ))
+                        .unwrap();
+                        (*i, c.clone(), partial)
+                    })
+                    .collect();
+                assert_eq!(verify_shares(key_info, &shares, &msg, tweak), Ok(()));
+
+                for blamed in 0..shares.len() {
+                    let mut tampered = shares.clone();
+                    tampered[blamed].2 .0 = tampered[blamed].2 .0 + Scalar::one();
+                    assert_eq!(
+                        verify_shares(key_info, &tampered, &msg, tweak),
+                        Err(InvalidShares {
+                            parties: vec![shares[blamed].0]
+                        }),
+                        "{:?} with tweak {}",
+                        mode,
+                        taproot_tweak
+                    );
+                }
+            }
+        }
+    }
+}
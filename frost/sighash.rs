@@ -0,0 +1,297 @@
+//! BIP-341 sighash computation.
+//!
+//! `run_frost_signing` signs whatever 32-byte hash it is given. This module
+//! builds the BIP-341 signature message for a Taproot input and returns the
+//! `TapSighash` tagged hash to pass as `message_hash`.
+
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::{Annex, Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::TapLeafHash;
+use bitcoin::{Transaction, TxOut};
+
+/// Tapleaf being spent on the script path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TapLeafSpend {
+    /// Hash of the tapleaf script and leaf version
+    pub leaf_hash: TapLeafHash,
+    /// Opcode position of the last executed `OP_CODESEPARATOR`
+    /// (`u32::MAX` if none)
+    pub code_separator_pos: u32,
+}
+
+impl TapLeafSpend {
+    /// Spend of `leaf_hash` without any executed `OP_CODESEPARATOR`.
+    pub fn new(leaf_hash: TapLeafHash) -> Self {
+        Self {
+            leaf_hash,
+            code_separator_pos: u32::MAX,
+        }
+    }
+}
+
+/// Compute the BIP-341 sighash for one input of `tx`.
+///
+/// `prevouts` must contain the output spent by every input, in input order.
+/// `annex` is the raw annex including its `0x50` prefix. Pass `leaf` for a
+/// script-path spend and `None` for a key-path spend.
+pub fn taproot_sighash(
+    tx: &Transaction,
+    prevouts: &[TxOut],
+    input_index: usize,
+    sighash_type: TapSighashType,
+    annex: Option<&[u8]>,
+    leaf: Option<TapLeafSpend>,
+) -> Result<[u8; 32], String> {
+    if input_index >= tx.input.len() {
+        return Err(format!(
+            "Input index {} out of range ({} inputs)",
+            input_index,
+            tx.input.len()
+        ));
+    }
+    if prevouts.len() != tx.input.len() {
+        return Err(format!(
+            "Expected {} prevouts, got {}",
+            tx.input.len(),
+            prevouts.len()
+        ));
+    }
+
+    let annex = annex
+        .map(Annex::new)
+        .transpose()
+        .map_err(|e| format!("Invalid annex: {}", e))?;
+    let leaf = leaf.map(|leaf| (leaf.leaf_hash, leaf.code_separator_pos));
+
+    let sighash = SighashCache::new(tx)
+        .taproot_signature_hash(
+            input_index,
+            &Prevouts::All(prevouts),
+            annex,
+            leaf,
+            sighash_type,
+        )
+        .map_err(|e| format!("Sighash error: {}", e))?;
+
+    Ok(sighash.to_byte_array())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::taproot::LeafVersion;
+    use bitcoin::{consensus, Amount, ScriptBuf};
+
+    /// Transaction and spent outputs of the BIP-341 wallet test vectors,
+    /// keyPathSpending[0].
+    fn bip341_spend() -> (Transaction, Vec<TxOut>) {
+        let raw_tx = hex::decode(
+            "02000000097de20cbff686da83a54981d2b9bab3586f4ca7e48f57f5b55963115f3b334e9c01000000\
+             0000000000d7b7cab57b1393ace2d064f4d4a2cb8af6def61273e127517d44759b6dafdd990000000000\
+             fffffffff8e1f583384333689228c5d28eac13366be082dc57441760d957275419a41842000000000\
+             0fffffffff0689180aa63b30cb162a73c6d2a38b7eeda2a83ece74310fda0843ad604853b0100000000\
+             feffffffaa5202bdf6d8ccd2ee0f0202afbbb7461d9264a25e5bfd3c5a52ee1239e0ba6c0000000000\
+             feffffff956149bdc66faa968eb2be2d2faa29718acbfe3941215893a2a3446d32acd0500000000000\
+             00000000e664b9773b88c09c32cb70a2a3e4da0ced63b7ba3b22f848531bbb1d5d5f4c9401000000000\
+             0000000e9aa6b8e6c9de67619e6a3924ae25696bb7b694bb677a632a74ef7eadfd4eabf0000000000\
+             ffffffffa778eb6a263dc090464cd125c466b5a99667720b1c110468831d058aa1b82af10100000000\
+             ffffffff0200ca9a3b000000001976a91406afd46bcdfd22ef94ac122aa11f241244a37ecc88ac8078\
+             40cb0000000020ac9a87f5594be208f8532db38cff670c450ed2fea8fcdefcc9a663f78bab962b0065cd1d",
+        )
+        .unwrap();
+        let tx: Transaction = consensus::deserialize(&raw_tx).unwrap();
+
+        let utxos = [
+            (
+                "512053a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343",
+                420000000,
+            ),
+            (
+                "5120147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3",
+                462000000,
+            ),
+            (
+                "76a914751e76e8199196d454941c45d1b3a323f1433bd688ac",
+                294000000,
+            ),
+            (
+                "5120e4d810fd50586274face62b8a807eb9719cef49c04177cc6b76a9a4251d5450e",
+                504000000,
+            ),
+            (
+                "512091b64d5324723a985170e4dc5a0f84c041804f2cd12660fa5dec09fc21783605",
+                630000000,
+            ),
+            ("00147dd65592d0ab2fe0d0257d571abf032cd9db93dc", 378000000),
+            (
+                "512075169f4001aa68f15bbed28b218df1d0a62cbbcf1188c6665110c293c907b831",
+                672000000,
+            ),
+            (
+                "5120712447206d7a5238acc7ff53fbe94a3b64539ad291c7cdbc490b7577e4b17df5",
+                546000000,
+            ),
+            (
+                "512077e30a5522dd9f894c3f8b8bd4c4b2cf82ca7da8a3ea6a239655c39c050ab220",
+                588000000,
+            ),
+        ];
+        let prevouts: Vec<TxOut> = utxos
+            .iter()
+            .map(|(script, sats)| TxOut {
+                value: Amount::from_sat(*sats),
+                script_pubkey: ScriptBuf::from_bytes(hex::decode(script).unwrap()),
+            })
+            .collect();
+        (tx, prevouts)
+    }
+
+    #[test]
+    fn test_taproot_sighash_bip341_key_path_vectors() {
+        let (tx, prevouts) = bip341_spend();
+
+        // (input index, hash type, expected sigHash)
+        let vectors = [
+            (
+                0,
+                0x03,
+                "2514a6272f85cfa0f45eb907fcb0d121b808ed37c6ea160a5a9046ed5526d555",
+            ),
+            (
+                1,
+                0x83,
+                "325a644af47e8a5a2591cda0ab0723978537318f10e6a63d4eed783b96a71a4d",
+            ),
+            (
+                3,
+                0x01,
+                "bf013ea93474aa67815b1b6cc441d23b64fa310911d991e713cd34c7f5d46669",
+            ),
+            (
+                4,
+                0x00,
+                "4f900a0bae3f1446fd48490c2958b5a023228f01661cda3496a11da502a7f7ef",
+            ),
+            (
+                6,
+                0x02,
+                "15f25c298eb5cdc7eb1d638dd2d45c97c4c59dcaec6679cfc16ad84f30876b85",
+            ),
+            (
+                7,
+                0x82,
+                "cd292de50313804dabe4685e83f923d2969577191a3e1d2882220dca88cbeb10",
+            ),
+            (
+                8,
+                0x81,
+                "cccb739eca6c13a8a89e6e5cd317ffe55669bbda23f2fd37b0f18755e008edd2",
+            ),
+        ];
+        for (input_index, hash_type, expected) in vectors {
+            let sighash_type = TapSighashType::from_consensus_u8(hash_type).unwrap();
+            let sighash =
+                taproot_sighash(&tx, &prevouts, input_index, sighash_type, None, None).unwrap();
+            assert_eq!(hex::encode(sighash), expected, "input {}", input_index);
+        }
+    }
+
+    #[test]
+    fn test_taproot_sighash_annex_and_script_path() {
+        // BIP-341 publishes key-path vectors only. These are the annex and
+        // script-path vectors from rust-bitcoin 0.32.5's own test suite
+        // (src/crypto/sighash.rs: test_sighashes_with_annex,
+        // test_sighashes_with_script_path, test_sighashes_with_annex_and_script),
+        // copied verbatim: unsigned transaction, consensus-encoded spent outputs,
+        // input 0, expected sighash, hash type, annex and tapleaf script.
+        let vectors = [
+            (
+                "0200000001df8123752e8f37d132c4e9f1ff7e4f9b986ade9211267e9ebd5fd22a5e718dec6d0100\
+                 0000ce4023b903cb7b23000000000017a914a18b36ea7a094db2f4940fc09edf154e86de7bd78758\
+                 0200000000000017a914afd0d512a2c5c2b40e25669e9cc460303c325b8b87580200000000000017\
+                 a914a18b36ea7a094db2f4940fc09edf154e86de7bd787f6020000",
+                "01ea49260000000000225120ab5e9800806bf18cb246edcf5fe63441208fe955a4b5a35bbff65f5d\
+                 b622a010",
+                "3b003000add359a364a156e73e02846782a59d0d95ca8c4638aaad99f2ef915c",
+                TapSighashType::SinglePlusAnyoneCanPay,
+                Some("507b979802e62d397acb29f56743a791894b99372872fc5af06a4f6e8d242d0615cda53062bb20e6\
+                      ec79756fe39183f0c128adfe85559a8fa042b042c018aa8010143799e44f0893c40e1e"),
+                None,
+            ),
+            (
+                "020000000189fc651483f9296b906455dd939813bf086b1bbe7c77635e157c8e14ae290621950100\
+                 00004445b5c7044561320000000000160014331414dbdada7fb578f700f38fb69995fc9b5ab95802\
+                 0000000000001976a914268db0a8104cc6d8afd91233cc8b3d1ace8ac3ef88ac5802000000000000\
+                 17a914ec00dcb368d6a693e11986d265f659d2f59e8be2875802000000000000160014c715799a49\
+                 a0bae3956df9c17cb4440a673ac0df6f010000",
+                "011bec34000000000022512028055142ea437db73382e991861446040b61dd2185c4891d7daf6893\
+                 d79f7182",
+                "d66de5274a60400c7b08c86ba6b7f198f40660079edf53aca89d2a9501317f2e",
+                TapSighashType::All,
+                None,
+                Some("20cc4e1107aea1d170c5ff5b6817e1303010049724fb3caa7941792ea9d29b3e2bacab"),
+            ),
+            (
+                "020000000132fb72cb8fba496755f027a9743e2d698c831fdb8304e4d1a346ac92cbf51acba50100\
+                 000026bdc7df044aad34000000000017a9144fa2554ed6174586854fa3bc01de58dcf33567d08758\
+                 02000000000000160014950367e1e62cdf240b35b883fc2f5e39f0eb9ab958020000000000001600\
+                 14950367e1e62cdf240b35b883fc2f5e39f0eb9ab958020000000000001600141b31217d48ccc876\
+                 0dcc0710fade5866d628e733a02d5122",
+                "011458360000000000225120a7baec3fb9f84614e3899fcc010c638f80f13539344120e1f4d8b68a\
+                 9a011a13",
+                "a0042aa434f9a75904b64043f2a283f8b4c143c7f4f7f49a6cbe5b9f745f4c15",
+                TapSighashType::All,
+                Some("50a6272b470e1460e3332ade7bb14b81671c564fb6245761bd5bd531394b28860e0b3808ab229fb5\
+                      1791fb6ae6fa82d915b2efb8f6df83ae1f5ab3db13e30928875e2a22b749d89358de481f19286cd4\
+                      caa792ce27f9559082d227a731c5486882cc707f83da361c51b7aadd9a0cf68fe7480c410fa137b4\
+                      54482d9a1ebf0f96d760b4d61426fc109c6e8e99a508372c45caa7b000a41f8251305da3f206c184\
+                      9985ba03f3d9592832b4053afbd23ab25d0465df0bc25a36c223aacf8e04ec736a418c72dc319e4d\
+                      a3e972e349713ca600965e7c665f2090d5a70e241ac164115a1f5639f28b1773327715ca307ace64\
+                      a2de7f0e3df70a2ffee3857689f909c0dad46d8a20fa373a4cc6eed6d4c9806bf146f0d76baae1"),
+                Some("7520ab9160dd8299dc1367659be3e8f66781fe440d52940c7f8d314a89b9f2698d406ead6ead6ead\
+                      6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead\
+                      6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead\
+                      6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6eadac"),
+            ),
+        ];
+        for (raw_tx, raw_prevouts, expected, sighash_type, annex, script) in vectors {
+            let tx: Transaction = consensus::deserialize(&hex::decode(raw_tx).unwrap()).unwrap();
+            let prevouts: Vec<TxOut> =
+                consensus::deserialize(&hex::decode(raw_prevouts).unwrap()).unwrap();
+            let annex = annex.map(|annex| hex::decode(annex).unwrap());
+            let leaf = script.map(|script| {
+                let script = ScriptBuf::from_bytes(hex::decode(script).unwrap());
+                TapLeafSpend::new(TapLeafHash::from_script(&script, LeafVersion::TapScript))
+            });
+            let sighash =
+                taproot_sighash(&tx, &prevouts, 0, sighash_type, annex.as_deref(), leaf).unwrap();
+            assert_eq!(hex::encode(sighash), expected);
+        }
+
+        // The position of the last OP_CODESEPARATOR is committed to
+        let (tx, prevouts) = bip341_spend();
+        let script = ScriptBuf::from_bytes(
+            hex::decode("20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac")
+                .unwrap(),
+        );
+        let leaf = TapLeafSpend::new(TapLeafHash::from_script(&script, LeafVersion::TapScript));
+        let without = taproot_sighash(&tx, &prevouts, 4, TapSighashType::Default, None, Some(leaf));
+        let with = taproot_sighash(
+            &tx,
+            &prevouts,
+            4,
+            TapSighashType::Default,
+            None,
+            Some(TapLeafSpend {
+                code_separator_pos: 1,
+                ..leaf
+            }),
+        );
+        assert_ne!(without.unwrap(), with.unwrap());
+
+        // The annex must start with 0x50
+        assert!(
+            taproot_sighash(&tx, &prevouts, 3, TapSighashType::All, Some(&[0x51]), None).is_err()
+        );
+    }
+}
@@ -6,14 +6,27 @@
 use async_channel::{Receiver, Sender};
 use pin_project_lite::pin_project;
 use rand::rngs::OsRng;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
 use std::pin::Pin;
 use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 use tracing::{debug, error, info};
 
+use bitcoin::secp256k1::{schnorr, Message, Secp256k1, XOnlyPublicKey};
+use bitcoin::sighash::TapSighashType;
+use bitcoin::taproot;
 use givre::ciphersuite::{Bitcoin, Ciphersuite};
 
+use super::batch::{run_frost_batch_signing, FrostBatchSigningResult};
+use super::ciphersuite::{load_key_share, FrostCiphersuite};
+use super::nonce_ledger::NonceLedgerHandle;
+use super::op_count;
+use super::rounds::{group_public_key, schnorr_signature, FrostKeyInfo};
+use super::taproot::{tweak_public_key, TaprootTweak};
+use super::timing::{TransportLog, TransportTimings};
+use super::transcript::SigningTranscript;
 use crate::bench::{BenchmarkRecorder, BenchmarkReport};
 
 /// Type alias for FROST signing message
@@ -31,23 +44,203 @@ pub struct ProtocolMessage {
     pub seq: u64,
 }
 
-/// Schnorr signature data (64 bytes for Taproot).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// secp256k1 field prime p, big-endian.
+const SECP256K1_FIELD_PRIME: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xfc, 0x2f,
+];
+
+/// secp256k1 group order n, big-endian.
+const SECP256K1_GROUP_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// BIP-340 Schnorr signature (64 bytes for Taproot).
+///
+/// Serializes as the 64-byte `R || s` encoding: a hex string for
+/// human-readable formats, raw bytes otherwise. Signatures can only be built
+/// through the strict parser, so `r < p` and `s < n` always hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SchnorrSignature {
     /// The signature R component (32 bytes, x-only)
-    pub r: Vec<u8>,
+    r: [u8; 32],
     /// The signature s component (32 bytes)
-    pub s: Vec<u8>,
+    s: [u8; 32],
 }
 
 impl SchnorrSignature {
+    /// The signature R component (32 bytes, x-only).
+    pub fn r(&self) -> &[u8; 32] {
+        &self.r
+    }
+
+    /// The signature s component (32 bytes).
+    pub fn s(&self) -> &[u8; 32] {
+        &self.s
+    }
+
     /// Convert to 64-byte Schnorr signature format for Bitcoin Taproot.
-    pub fn to_bytes(&self) -> Vec<u8> {
-        let mut sig = Vec::with_capacity(64);
-        sig.extend_from_slice(&self.r);
-        sig.extend_from_slice(&self.s);
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut sig = [0u8; 64];
+        sig[..32].copy_from_slice(&self.r);
+        sig[32..].copy_from_slice(&self.s);
         sig
     }
+
+    /// Verify this signature per BIP-340 against an x-only public key.
+    pub fn verify(&self, xonly_pubkey: &[u8], msg32: &[u8; 32]) -> Result<(), String> {
+        let pubkey = XOnlyPublicKey::from_slice(xonly_pubkey)
+            .map_err(|e| format!("Invalid x-only public key: {}", e))?;
+        let msg = Message::from_digest(*msg32);
+
+        Secp256k1::verification_only()
+            .verify_schnorr(&self.to_secp256k1(), &msg, &pubkey)
+            .map_err(|e| format!("BIP-340 verification failed: {}", e))
+    }
+
+    /// Convert to the `secp256k1` crate's Schnorr signature type.
+    pub fn to_secp256k1(&self) -> schnorr::Signature {
+        // Only the length is checked, and `to_bytes` always returns 64 bytes
+        schnorr::Signature::from_slice(&self.to_bytes()).expect("signature is 64 bytes")
+    }
+
+    /// Convert to a Taproot witness signature with the given sighash type.
+    pub fn to_taproot_signature(&self, sighash_type: TapSighashType) -> taproot::Signature {
+        taproot::Signature {
+            signature: self.to_secp256k1(),
+            sighash_type,
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for SchnorrSignature {
+    type Error = String;
+
+    /// Parse a 64-byte BIP-340 signature, rejecting r >= p and s >= n.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let bytes: &[u8; 64] = bytes
+            .try_into()
+            .map_err(|_| format!("Expected 64-byte signature, got {}", bytes.len()))?;
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&bytes[..32]);
+        s.copy_from_slice(&bytes[32..]);
+
+        // Big-endian byte order makes lexicographic comparison numeric
+        if r >= SECP256K1_FIELD_PRIME {
+            return Err("Signature R is not a valid field element (r >= p)".to_string());
+        }
+        if s >= SECP256K1_GROUP_ORDER {
+            return Err("Signature s is not a valid scalar (s >= n)".to_string());
+        }
+        Ok(Self { r, s })
+    }
+}
+
+impl TryFrom<schnorr::Signature> for SchnorrSignature {
+    type Error = String;
+
+    /// `secp256k1` accepts any 64 bytes, so the range checks still apply.
+    fn try_from(sig: schnorr::Signature) -> Result<Self, Self::Error> {
+        Self::try_from(&sig.serialize()[..])
+    }
+}
+
+impl From<SchnorrSignature> for schnorr::Signature {
+    fn from(sig: SchnorrSignature) -> Self {
+        sig.to_secp256k1()
+    }
+}
+
+impl std::fmt::Display for SchnorrSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(self.to_bytes()))
+    }
+}
+
+impl std::str::FromStr for SchnorrSignature {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|e| format!("Invalid signature hex: {}", e))?;
+        Self::try_from(bytes.as_slice())
+    }
+}
+
+impl Serialize for SchnorrSignature {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_string())
+        } else {
+            serializer.serialize_bytes(&self.to_bytes())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SchnorrSignature {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let hex_str = String::deserialize(deserializer)?;
+            hex_str.parse().map_err(serde::de::Error::custom)
+        } else {
+            let bytes = Vec::<u8>::deserialize(deserializer)?;
+            Self::try_from(bytes.as_slice()).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+/// Which Taproot spend path a signature is produced for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SigningMode {
+    /// Key-path spend: sign with the BIP-341 tweaked output key
+    #[default]
+    KeyPath,
+    /// Script-path spend: sign a tapleaf sighash with the untweaked internal
+    /// key, as used by `<pk> OP_CHECKSIG` leaves
+    ScriptPath,
+}
+
+/// What the 32-byte hash handed to a signing session is.
+///
+/// Off-chain message digests are only signed through
+/// [`run_frost_message_signing`](super::message::run_frost_message_signing),
+/// which computes the digest itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SignedHash {
+    /// BIP-341 sighash of a transaction input
+    Sighash,
+    /// Digest of an off-chain message, signed with the untweaked group key
+    MessageDigest,
+}
+
+impl SigningMode {
+    /// Whether signatures in this mode are made with the BIP-341 tweaked key.
+    pub fn is_tweaked(&self) -> bool {
+        matches!(self, SigningMode::KeyPath)
+    }
+}
+
+/// Options controlling a FROST signing session.
+#[derive(Debug, Clone, Default)]
+pub struct SigningOptions {
+    /// Key-path (tweaked) or script-path (untweaked) signing
+    pub mode: SigningMode,
+    /// BIP-341 tweak of the output key: a script-tree merkle root, no script
+    /// tree (BIP-86 style) or a raw tweak scalar. Must be
+    /// [`TaprootTweak::KeyOnly`] in untweaked modes.
+    pub taproot_tweak: TaprootTweak,
+    /// Record per-step timings and return a `BenchmarkReport`
+    pub enable_benchmark: bool,
+    /// Verify the aggregated signature against the output key before
+    /// reporting success. The batch, presigned and coordinator flows always
+    /// verify, blaming invalid partial signatures if it does not verify.
+    pub verify_signature: bool,
+    /// Ledger that records every nonce used by the round-by-round signing
+    /// flows (batch, presigned, coordinator) and refuses reuse. Givre's
+    /// interactive protocol generates fresh nonces per session and does not
+    /// use it.
+    pub nonce_ledger: Option<NonceLedgerHandle>,
 }
 
 /// Result of FROST signing.
@@ -56,24 +249,66 @@ pub struct FrostSigningResult {
     pub success: bool,
     /// The Schnorr signature (64 bytes)
     pub signature: Option<SchnorrSignature>,
+    /// The x-only key the signature verifies against: the tweaked output key
+    /// for key-path signing, the internal key for script-path signing
+    pub output_key: Option<[u8; 32]>,
     /// Error message if failed
     pub error: Option<String>,
     /// Duration of the protocol
     pub duration_secs: f64,
     /// Detailed benchmark report (if benchmarking enabled)
     pub benchmark: Option<BenchmarkReport>,
+    /// Signers (index at keygen) whose partial signatures failed verification.
+    /// Only the round-by-round flows verify individual shares.
+    pub blamed_parties: Vec<u16>,
+    /// Public record of the session for offline verification
+    pub transcript: Option<SigningTranscript>,
+}
+
+impl From<FrostBatchSigningResult> for FrostSigningResult {
+    /// Result of a batch session over a single message.
+    fn from(mut batch: FrostBatchSigningResult) -> Self {
+        Self {
+            success: batch.success,
+            signature: batch.signatures.pop(),
+            output_key: batch.output_key,
+            error: batch.error,
+            duration_secs: batch.duration_secs,
+            benchmark: batch.benchmark,
+            blamed_parties: batch.blamed_parties,
+            transcript: batch.transcripts.pop(),
+        }
+    }
 }
 
 pin_project! {
     /// Wrapper to adapt our async channels to round_based Stream.
-    pub struct ChannelStream {
+    pub struct ChannelStream<M> {
         #[pin]
         receiver: Receiver<ProtocolMessage>,
+        timings: Option<TransportLog>,
+        _msg: PhantomData<M>,
+    }
+}
+
+impl<M> ChannelStream<M> {
+    pub fn new(receiver: Receiver<ProtocolMessage>) -> Self {
+        Self {
+            receiver,
+            timings: None,
+            _msg: PhantomData,
+        }
+    }
+
+    /// Timestamp every received message in `timings`.
+    pub(crate) fn with_timings(mut self, timings: TransportLog) -> Self {
+        self.timings = Some(timings);
+        self
     }
 }
 
-impl futures::Stream for ChannelStream {
-    type Item = Result<round_based::Incoming<FrostSigningMsg>, std::io::Error>;
+impl<M: DeserializeOwned> futures::Stream for ChannelStream<M> {
+    type Item = Result<round_based::Incoming<M>, std::io::Error>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.project();
@@ -81,6 +316,10 @@ impl futures::Stream for ChannelStream {
         match this.receiver.poll_next(cx) {
             Poll::Ready(Some(msg)) => match serde_json::from_slice(&msg.payload) {
                 Ok(protocol_msg) => {
+                    if let Some(Ok(mut timings)) = this.timings.as_ref().map(|t| t.lock()) {
+                        // round_based numbers rounds from 0
+                        timings.received(msg.round + 1, msg.sender);
+                    }
                     let incoming = round_based::Incoming {
                         id: msg.seq,
                         sender: msg.sender,
@@ -114,27 +353,46 @@ pin_project! {
         session_id: String,
         party_index: u16,
         seq: u64,
+        timings: Option<TransportLog>,
     }
 }
 
-impl futures::Sink<round_based::Outgoing<FrostSigningMsg>> for ChannelSink {
+impl ChannelSink {
+    pub fn new(sender: Sender<ProtocolMessage>, session_id: &str, party_index: u16) -> Self {
+        Self {
+            sender,
+            session_id: session_id.to_string(),
+            party_index,
+            seq: 0,
+            timings: None,
+        }
+    }
+
+    /// Timestamp every sent message in `timings`.
+    pub(crate) fn with_timings(mut self, timings: TransportLog) -> Self {
+        self.timings = Some(timings);
+        self
+    }
+}
+
+impl<M: Serialize + round_based::ProtocolMessage> futures::Sink<round_based::Outgoing<M>>
+    for ChannelSink
+{
     type Error = std::io::Error;
 
     fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         Poll::Ready(Ok(()))
     }
 
-    fn start_send(
-        self: Pin<&mut Self>,
-        item: round_based::Outgoing<FrostSigningMsg>,
-    ) -> Result<(), Self::Error> {
+    fn start_send(self: Pin<&mut Self>, item: round_based::Outgoing<M>) -> Result<(), Self::Error> {
         let this = self.project();
         *this.seq += 1;
         let seq = *this.seq;
 
-        let (recipient, round) = match &item.recipient {
-            round_based::MessageDestination::AllParties => (None, 0),
-            round_based::MessageDestination::OneParty(p) => (Some(*p), 0),
+        let round = item.msg.round();
+        let recipient = match &item.recipient {
+            round_based::MessageDestination::AllParties => None,
+            round_based::MessageDestination::OneParty(p) => Some(*p),
         };
 
         let payload = serde_json::to_vec(&item.msg)
@@ -152,6 +410,10 @@ impl futures::Sink<round_based::Outgoing<FrostSigningMsg>> for ChannelSink {
         this.sender
             .try_send(msg)
             .map_err(|e| std::io::Error::other(e.to_string()))?;
+        if let Some(Ok(mut timings)) = this.timings.as_ref().map(|t| t.lock()) {
+            timings.sent(round + 1);
+        }
+        op_count::mark_round(round + 1);
 
         Ok(())
     }
@@ -173,6 +435,9 @@ pub type FrostKeyShare = givre::KeyShare<<Bitcoin as Ciphersuite>::Curve>;
 ///
 /// This produces a Schnorr signature that can be used in Taproot Bitcoin
 /// transactions. The signature is 64 bytes (R || s).
+///
+/// `message_hash` must be the BIP-341 sighash of the input being signed;
+/// use [`taproot_sighash`](super::sighash::taproot_sighash) to compute it.
 pub async fn run_frost_signing(
     party_index: u16,
     parties_at_keygen: &[u16],
@@ -207,11 +472,113 @@ pub async fn run_frost_signing_with_benchmark(
     outgoing_tx: Sender<ProtocolMessage>,
     enable_benchmark: bool,
 ) -> FrostSigningResult {
+    let options = SigningOptions {
+        enable_benchmark,
+        verify_signature: true,
+        ..Default::default()
+    };
+    run_frost_signing_with_options(
+        party_index,
+        parties_at_keygen,
+        session_id,
+        message_hash,
+        key_share_data,
+        &options,
+        incoming_rx,
+        outgoing_tx,
+    )
+    .await
+}
+
+/// Derive the x-only key a signature made with `options` verifies against.
+pub fn verifying_key(
+    key_info: &FrostKeyInfo,
+    options: &SigningOptions,
+) -> Result<[u8; 32], String> {
+    let pubkey_bytes = group_public_key(key_info);
+    match options.mode {
+        SigningMode::KeyPath => {
+            tweak_public_key(&pubkey_bytes[1..], options.taproot_tweak).map(|(key, _parity)| key)
+        }
+        _ if options.taproot_tweak != TaprootTweak::KeyOnly => Err(format!(
+            "Taproot tweak must not be set in {:?} mode",
+            options.mode
+        )),
+        SigningMode::ScriptPath => pubkey_bytes[1..]
+            .try_into()
+            .map_err(|_| format!("Unexpected public key length: {}", pubkey_bytes.len())),
+    }
+}
+
+/// Run FROST threshold signing with explicit signing options.
+///
+/// The signature is produced under the BIP-341 tweaked key given by
+/// `options.taproot_tweak`, and the tweaked output key is returned alongside
+/// it. Givre's signing protocol only takes a merkle root, so a raw tweak
+/// scalar is signed through Givre's round API as a batch of one message
+/// (see [`run_frost_batch_signing`]).
+#[allow(clippy::too_many_arguments)]
+pub async fn run_frost_signing_with_options(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    message_hash: &[u8; 32],
+    key_share_data: &[u8],
+    options: &SigningOptions,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostSigningResult {
+    run_frost_signing_session(
+        party_index,
+        parties_at_keygen,
+        session_id,
+        message_hash,
+        SignedHash::Sighash,
+        key_share_data,
+        options,
+        incoming_rx,
+        outgoing_tx,
+    )
+    .await
+}
+
+/// Run one FROST signing session over `message_hash`, which is a `kind`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_frost_signing_session(
+    party_index: u16,
+    parties_at_keygen: &[u16],
+    session_id: &str,
+    message_hash: &[u8; 32],
+    kind: SignedHash,
+    key_share_data: &[u8],
+    options: &SigningOptions,
+    incoming_rx: Receiver<ProtocolMessage>,
+    outgoing_tx: Sender<ProtocolMessage>,
+) -> FrostSigningResult {
+    if options.mode.is_tweaked() && matches!(options.taproot_tweak, TaprootTweak::Scalar(_)) {
+        return run_frost_batch_signing(
+            party_index,
+            parties_at_keygen,
+            session_id,
+            &[*message_hash],
+            key_share_data,
+            options,
+            incoming_rx,
+            outgoing_tx,
+        )
+        .await
+        .into();
+    }
+
+    let enable_benchmark = options.enable_benchmark;
     let start = std::time::Instant::now();
 
     // Initialize benchmark recorder
     let recorder = Arc::new(Mutex::new(BenchmarkRecorder::new(
-        "FROST-Signing",
+        match kind {
+            SignedHash::Sighash => "FROST-Signing",
+            SignedHash::MessageDigest => "FROST-Message-Signing",
+        },
         party_index,
         session_id,
     )));
@@ -222,7 +589,8 @@ pub async fn run_frost_signing_with_benchmark(
     info!("Party index: {}", party_index);
     info!("Parties at keygen: {:?}", parties_at_keygen);
     info!("Session ID: {}", session_id);
-    info!("Message hash: {}", hex::encode(message_hash));
+    info!("Message hash ({:?}): {}", kind, hex::encode(message_hash));
+    info!("Taproot tweak: {}", options.taproot_tweak);
     info!(
         "Benchmarking: {}",
         if enable_benchmark {
@@ -234,16 +602,19 @@ pub async fn run_frost_signing_with_benchmark(
 
     // Step 1: Deserialize the key share
     let step_start = std::time::Instant::now();
-    let key_share: FrostKeyShare = match serde_json::from_slice(key_share_data) {
+    let key_share: FrostKeyShare = match load_key_share(key_share_data, FrostCiphersuite::Bitcoin) {
         Ok(ks) => ks,
         Err(e) => {
-            error!("Failed to deserialize key share: {}", e);
+            error!("Failed to load key share: {}", e);
             return FrostSigningResult {
                 success: false,
                 signature: None,
-                error: Some(format!("Key share deserialization error: {}", e)),
+                output_key: None,
+                error: Some(e),
                 duration_secs: start.elapsed().as_secs_f64(),
                 benchmark: None,
+                blamed_parties: Vec::new(),
+                transcript: None,
             };
         }
     };
@@ -261,184 +632,183 @@ pub async fn run_frost_signing_with_benchmark(
         hex::encode(&pubkey_bytes)
     );
 
-    // Step 2: Create Stream and Sink adapters
-    let step_start = std::time::Instant::now();
-    let incoming_stream = ChannelStream {
-        receiver: incoming_rx,
-    };
-
-    let outgoing_sink = ChannelSink {
-        sender: outgoing_tx,
-        session_id: session_id.to_string(),
-        party_index,
-        seq: 0,
-    };
-
-    // Box the stream and sink for the MpcParty
-    let incoming_boxed = Box::pin(incoming_stream);
-    let outgoing_boxed = Box::pin(outgoing_sink);
-
-    // Create the MPC party
-    let party = round_based::MpcParty::connected((incoming_boxed, outgoing_boxed));
-    if enable_benchmark {
-        if let Ok(mut rec) = recorder.lock() {
-            rec.record_step("2. Protocol setup (channels, party)", step_start.elapsed());
+    // Derive the key the signature will verify against
+    let output_key = match kind {
+        SignedHash::MessageDigest if options.mode.is_tweaked() => {
+            Err("Message digests are signed with the untweaked group key".to_string())
         }
-    }
-
-    // Step 3: Create signing builder
-    let step_start = std::time::Instant::now();
-    info!("Starting FROST signing protocol (Bitcoin/BIP-340 ciphersuite)...");
-    let signing_builder =
-        givre::signing::<Bitcoin>(party_index, &key_share, parties_at_keygen, message_hash);
-    if enable_benchmark {
-        if let Ok(mut rec) = recorder.lock() {
-            rec.record_step("3. Create signing builder", step_start.elapsed());
-        }
-    }
-
-    // Step 4: Set taproot tweak
-    let step_start = std::time::Instant::now();
-    let signing_builder = match signing_builder.set_taproot_tweak(None) {
-        Ok(builder) => builder,
+        _ => verifying_key(key_share.as_ref(), options),
+    };
+    let output_key = match output_key {
+        Ok(key) => key,
         Err(e) => {
-            error!("Failed to set taproot tweak: {:?}", e);
+            error!("Failed to derive taproot output key: {}", e);
             return FrostSigningResult {
                 success: false,
                 signature: None,
-                error: Some(format!("Failed to set taproot tweak: {:?}", e)),
+                output_key: None,
+                error: Some(e),
                 duration_secs: start.elapsed().as_secs_f64(),
                 benchmark: None,
+                blamed_parties: Vec::new(),
+                transcript: None,
             };
         }
     };
-    if enable_benchmark {
-        if let Ok(mut rec) = recorder.lock() {
-            rec.record_step("4. Set taproot tweak (BIP-341)", step_start.elapsed());
-        }
+    info!(
+        "Signing mode: {:?}, verifying key: {}",
+        options.mode,
+        hex::encode(output_key)
+    );
+
+    if parties_at_keygen.get(usize::from(party_index)) != Some(&key_share.i) {
+        let e = format!(
+            "Party index {} does not match key share index {} in signers {:?}",
+            party_index, key_share.i, parties_at_keygen
+        );
+        error!("{}", e);
+        return FrostSigningResult {
+            success: false,
+            signature: None,
+            output_key: None,
+            error: Some(e),
+            duration_secs: start.elapsed().as_secs_f64(),
+            benchmark: None,
+            blamed_parties: Vec::new(),
+            transcript: None,
+        };
     }
 
-    // Step 5: Run the signing protocol (main MPC computation)
-    let step_start = std::time::Instant::now();
-    let signing_result = signing_builder.sign(&mut OsRng, party).await;
-    if enable_benchmark {
-        if let Ok(mut rec) = recorder.lock() {
-            rec.record_step("5. MPC signing protocol", step_start.elapsed());
+    let record = |step: &str, elapsed: std::time::Duration| {
+        if enable_benchmark {
+            if let Ok(mut rec) = recorder.lock() {
+                rec.record_step(step, elapsed);
+            }
+        }
+    };
+
+    let result: Result<SchnorrSignature, String> = async {
+        // Step 2: Create Stream and Sink adapters
+        let step_start = std::time::Instant::now();
+        let transport_log = TransportLog::default();
+        let mut incoming_stream: ChannelStream<FrostSigningMsg> = ChannelStream::new(incoming_rx);
+        let mut outgoing_sink = ChannelSink::new(outgoing_tx, session_id, party_index);
+        if enable_benchmark {
+            incoming_stream = incoming_stream.with_timings(transport_log.clone());
+            outgoing_sink = outgoing_sink.with_timings(transport_log.clone());
+        }
+        let party =
+            round_based::MpcParty::connected((Box::pin(incoming_stream), Box::pin(outgoing_sink)));
+        record("2. Protocol setup (channels, party)", step_start.elapsed());
+
+        // Step 3: Create signing builder
+        let step_start = std::time::Instant::now();
+        let signing_builder =
+            givre::signing::<Bitcoin>(party_index, &key_share, parties_at_keygen, message_hash);
+        record("3. Create signing builder", step_start.elapsed());
+
+        // Step 4: Set taproot tweak (untweaked modes sign with the internal key)
+        let step_start = std::time::Instant::now();
+        let signing_builder = if options.mode.is_tweaked() {
+            let builder = signing_builder
+                .set_taproot_tweak(options.taproot_tweak.merkle_root())
+                .map_err(|e| format!("Failed to set taproot tweak: {:?}", e))?;
+            record("4. Set taproot tweak (BIP-341)", step_start.elapsed());
+            builder
+        } else {
+            signing_builder
+        };
+
+        // Step 5: Run the signing protocol (main MPC computation)
+        info!("Starting FROST signing protocol (Bitcoin/BIP-340 ciphersuite)...");
+        let step_start = std::time::Instant::now();
+        if let Ok(mut timings) = transport_log.lock() {
+            *timings = TransportTimings::new();
+        }
+        let signature = signing_builder
+            .sign(&mut OsRng, party)
+            .await
+            .map_err(|e| format!("Protocol error: {:?}", e))?;
+        let protocol_end = std::time::Instant::now();
+        record("5. MPC signing protocol", step_start.elapsed());
+        if let Ok(timings) = transport_log.lock() {
+            timings.record(5, protocol_end, record);
         }
+
+        // Step 6: Extract signature components
+        let step_start = std::time::Instant::now();
+        let signature = schnorr_signature(&signature)?;
+        record("6. Extract signature components", step_start.elapsed());
+
+        // Step 7: Self-verify so a bad aggregation never leaves the node
+        if options.verify_signature {
+            let step_start = std::time::Instant::now();
+            signature
+                .verify(&output_key, message_hash)
+                .map_err(|e| format!("Signature self-verification failed: {}", e))?;
+            record("7. Verify signature (BIP-340)", step_start.elapsed());
+            info!("Signature verified against output key");
+        }
+        Ok(signature)
     }
+    .await;
 
     let elapsed = start.elapsed();
 
-    match signing_result {
+    // Complete benchmark, even on failure
+    let benchmark_report = if enable_benchmark {
+        if let Ok(mut rec) = recorder.lock() {
+            rec.complete();
+            let report = rec.report();
+            report.log();
+            Some(report)
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    match result {
         Ok(signature) => {
             info!(
                 "FROST signing completed successfully in {:.2}s",
                 elapsed.as_secs_f64()
             );
-
-            // Step 6: Extract signature components
-            let step_start = std::time::Instant::now();
-            let r_point_bytes: Vec<u8> = signature.r.to_bytes().into();
-            let r = if r_point_bytes.len() == 33 {
-                debug!(
-                    "R point in compressed format, prefix: 0x{:02x}",
-                    r_point_bytes[0]
-                );
-                r_point_bytes[1..33].to_vec()
-            } else if r_point_bytes.len() == 32 {
-                debug!("R point already in x-only format");
-                r_point_bytes
-            } else {
-                error!("Unexpected R point length: {}", r_point_bytes.len());
-                return FrostSigningResult {
-                    success: false,
-                    signature: None,
-                    error: Some(format!(
-                        "Unexpected R point length: {}",
-                        r_point_bytes.len()
-                    )),
-                    duration_secs: elapsed.as_secs_f64(),
-                    benchmark: None,
-                };
-            };
-
-            let z_bytes = signature.z.to_be_bytes();
-            let s = z_bytes.as_ref().to_vec();
-
-            if r.len() != 32 || s.len() != 32 {
-                error!(
-                    "Unexpected signature component lengths: R={}, s={}",
-                    r.len(),
-                    s.len()
-                );
-                return FrostSigningResult {
-                    success: false,
-                    signature: None,
-                    error: Some(format!(
-                        "Unexpected signature lengths: R={}, s={}",
-                        r.len(),
-                        s.len()
-                    )),
-                    duration_secs: elapsed.as_secs_f64(),
-                    benchmark: None,
-                };
-            }
-            if enable_benchmark {
-                if let Ok(mut rec) = recorder.lock() {
-                    rec.record_step("6. Extract signature components", step_start.elapsed());
-                }
-            }
-
-            info!("BIP-340 Signature R: {}", hex::encode(&r));
-            info!("BIP-340 Signature s: {}", hex::encode(&s));
-
-            let schnorr_sig = SchnorrSignature { r, s };
-
-            // Complete benchmark and generate report
-            let benchmark_report = if enable_benchmark {
-                if let Ok(mut rec) = recorder.lock() {
-                    rec.complete();
-                    let report = rec.report();
-                    report.log();
-                    Some(report)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+            info!("BIP-340 Signature R: {}", hex::encode(signature.r()));
+            info!("BIP-340 Signature s: {}", hex::encode(signature.s()));
 
             FrostSigningResult {
                 success: true,
-                signature: Some(schnorr_sig),
+                signature: Some(signature),
+                output_key: Some(output_key),
                 error: None,
                 duration_secs: elapsed.as_secs_f64(),
                 benchmark: benchmark_report,
+                blamed_parties: Vec::new(),
+                transcript: Some(SigningTranscript::new(
+                    session_id,
+                    parties_at_keygen,
+                    message_hash,
+                    key_share.as_ref(),
+                    options,
+                    signature,
+                    output_key,
+                )),
             }
         }
         Err(e) => {
-            error!("FROST signing failed: {:?}", e);
-
-            // Complete benchmark even on failure
-            let benchmark_report = if enable_benchmark {
-                if let Ok(mut rec) = recorder.lock() {
-                    rec.complete();
-                    let report = rec.report();
-                    report.log();
-                    Some(report)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+            error!("FROST signing failed: {}", e);
 
             FrostSigningResult {
                 success: false,
                 signature: None,
-                error: Some(format!("Protocol error: {:?}", e)),
+                output_key: None,
+                error: Some(e),
                 duration_secs: elapsed.as_secs_f64(),
                 benchmark: benchmark_report,
+                blamed_parties: Vec::new(),
+                transcript: None,
             }
         }
     }
@@ -446,13 +816,14 @@ pub async fn run_frost_signing_with_benchmark(
 
 #[cfg(test)]
 mod tests {
+    use super::super::local;
     use super::*;
 
     #[test]
     fn test_schnorr_signature_to_bytes() {
         let sig = SchnorrSignature {
-            r: vec![1u8; 32],
-            s: vec![2u8; 32],
+            r: [1u8; 32],
+            s: [2u8; 32],
         };
 
         let bytes = sig.to_bytes();
@@ -460,4 +831,113 @@ mod tests {
         assert_eq!(&bytes[..32], &[1u8; 32]);
         assert_eq!(&bytes[32..], &[2u8; 32]);
     }
+
+    #[test]
+    fn test_schnorr_signature_verify_bip340_vector() {
+        // BIP-340 test vector 0
+        let pubkey =
+            hex::decode("f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9")
+                .unwrap();
+        let sig: SchnorrSignature = "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca8215\
+                                     25f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0"
+            .parse()
+            .unwrap();
+
+        assert!(sig.verify(&pubkey, &[0u8; 32]).is_ok());
+        assert!(sig.verify(&pubkey, &[1u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_schnorr_signature_try_from_rejects_out_of_range() {
+        let mut bytes = [0u8; 64];
+        bytes[32..].copy_from_slice(&SECP256K1_GROUP_ORDER);
+        assert!(SchnorrSignature::try_from(&bytes[..]).is_err());
+
+        bytes[..32].copy_from_slice(&SECP256K1_FIELD_PRIME);
+        bytes[32..].fill(0);
+        assert!(SchnorrSignature::try_from(&bytes[..]).is_err());
+
+        assert!(SchnorrSignature::try_from(&bytes[..63]).is_err());
+
+        // secp256k1 only checks the length
+        let unchecked = schnorr::Signature::from_slice(&bytes).unwrap();
+        assert!(SchnorrSignature::try_from(unchecked).is_err());
+    }
+
+    #[test]
+    fn test_schnorr_signature_serde_hex_roundtrip() {
+        let sig = SchnorrSignature {
+            r: [1u8; 32],
+            s: [2u8; 32],
+        };
+
+        let json = serde_json::to_string(&sig).unwrap();
+        assert_eq!(json, format!("\"{}\"", hex::encode(sig.to_bytes())));
+        let decoded: SchnorrSignature = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, sig);
+    }
+
+    #[test]
+    fn test_script_path_signature_verifies_against_odd_y_internal_key() {
+        let group_key = |key_share_data: &[u8]| {
+            let key_share: FrostKeyShare =
+                load_key_share(key_share_data, FrostCiphersuite::Bitcoin).unwrap();
+            key_share.shared_public_key().to_bytes(true).to_vec()
+        };
+        // BIP-340 negates odd-y keys, so that is the case the shares must handle
+        let key_shares = (0..32)
+            .map(|_| local::key_shares(2, 3))
+            .find(|shares| group_key(&shares[0])[0] == 0x03)
+            .expect("no odd-y group key in 32 keygens");
+        let internal_key = group_key(&key_shares[0])[1..].to_vec();
+
+        let options = SigningOptions {
+            mode: SigningMode::ScriptPath,
+            verify_signature: true,
+            ..Default::default()
+        };
+        let message_hash = [0x42u8; 32];
+        for result in local::run_signing(&key_shares, &[0, 2], &message_hash, &options) {
+            assert!(result.success, "signing failed: {:?}", result.error);
+            assert_eq!(
+                result.output_key.map(|k| k.to_vec()),
+                Some(internal_key.clone())
+            );
+            assert!(result
+                .signature
+                .unwrap()
+                .verify(&internal_key, &message_hash)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_key_path_signing_with_merkle_root_and_raw_tweak() {
+        let key_shares = local::key_shares(2, 3);
+        let key_share: FrostKeyShare =
+            load_key_share(&key_shares[0], FrostCiphersuite::Bitcoin).unwrap();
+        let internal_key = group_public_key(key_share.as_ref())[1..].to_vec();
+
+        let message_hash = [0x42u8; 32];
+        for taproot_tweak in [
+            TaprootTweak::MerkleRoot([7u8; 32]),
+            TaprootTweak::Scalar([3u8; 32]),
+        ] {
+            let (expected_key, _parity) = tweak_public_key(&internal_key, taproot_tweak).unwrap();
+            let options = SigningOptions {
+                taproot_tweak,
+                verify_signature: true,
+                ..Default::default()
+            };
+            for result in local::run_signing(&key_shares, &[0, 1], &message_hash, &options) {
+                assert!(result.success, "signing failed: {:?}", result.error);
+                assert_eq!(result.output_key, Some(expected_key));
+                assert!(result
+                    .signature
+                    .unwrap()
+                    .verify(&expected_key, &message_hash)
+                    .is_ok());
+            }
+        }
+    }
 }
@@ -0,0 +1,235 @@
+//! Taproot output derivation for FROST group keys.
+//!
+//! This module turns the x-only public key produced by FROST keygen into the
+//! BIP-341 tweaked output key, its scriptPubKey and a bech32m address, so
+//! callers don't have to reimplement the tweak themselves.
+
+use bitcoin::hashes::Hash;
+use bitcoin::key::{Parity, Secp256k1, TweakedPublicKey, XOnlyPublicKey};
+use bitcoin::secp256k1::Scalar;
+use bitcoin::taproot::{TapNodeHash, TapTweakHash};
+use bitcoin::{Address, ScriptBuf};
+use serde::{Deserialize, Serialize};
+
+pub use bitcoin::Network;
+
+use super::ciphersuite::FrostCiphersuite;
+use super::keygen::FrostKeygenResult;
+
+/// Taproot output derived from a FROST group key.
+#[derive(Debug, Clone)]
+pub struct TaprootOutput {
+    /// The untweaked internal key (x-only, 32 bytes)
+    pub internal_key: [u8; 32],
+    /// The script-tree merkle root committed to, if any
+    pub merkle_root: Option<[u8; 32]>,
+    /// The BIP-341 tweaked output key (x-only, 32 bytes)
+    pub output_key: [u8; 32],
+    /// Parity of the full tweaked output key (needed for control blocks)
+    pub parity: Parity,
+    /// The segwit v1 scriptPubKey (`OP_1 <output_key>`)
+    pub script_pubkey: ScriptBuf,
+    /// The bech32m encoded address
+    pub address: String,
+}
+
+/// BIP-341 tweak applied to the internal key for key-path signing.
+///
+/// The output key is `Q = P + t·G`. Usually `t` is the TapTweak hash of the
+/// internal key and an optional script-tree merkle root; callers that derive
+/// `t` some other way can pass the scalar directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TaprootTweak {
+    /// Commit to no script tree (BIP-86): `t = H_TapTweak(P)`
+    #[default]
+    KeyOnly,
+    /// Commit to a script tree: `t = H_TapTweak(P || merkle_root)`
+    MerkleRoot([u8; 32]),
+    /// Tweak scalar `t` (32 bytes, big-endian, below the group order)
+    Scalar([u8; 32]),
+}
+
+impl TaprootTweak {
+    /// Tweak committing to `merkle_root`, or to no script tree if `None`.
+    pub fn from_merkle_root(merkle_root: Option<[u8; 32]>) -> Self {
+        merkle_root.map_or(TaprootTweak::KeyOnly, TaprootTweak::MerkleRoot)
+    }
+
+    /// The script-tree merkle root committed to, if any.
+    pub fn merkle_root(&self) -> Option<[u8; 32]> {
+        match self {
+            TaprootTweak::MerkleRoot(root) => Some(*root),
+            TaprootTweak::KeyOnly | TaprootTweak::Scalar(_) => None,
+        }
+    }
+
+    /// Tweak scalar `t` for the x-only `internal_key` (big-endian).
+    pub fn scalar(&self, internal_key: &[u8]) -> Result<[u8; 32], String> {
+        match self {
+            TaprootTweak::Scalar(t) => {
+                Scalar::from_be_bytes(*t)
+                    .map_err(|_| "Taproot tweak is not a valid scalar (t >= n)".to_string())?;
+                Ok(*t)
+            }
+            TaprootTweak::KeyOnly | TaprootTweak::MerkleRoot(_) => {
+                let internal_key = XOnlyPublicKey::from_slice(internal_key)
+                    .map_err(|e| format!("Invalid internal key: {}", e))?;
+                let merkle_root = self.merkle_root().map(TapNodeHash::from_byte_array);
+                Ok(TapTweakHash::from_key_and_tweak(internal_key, merkle_root).to_byte_array())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for TaprootTweak {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaprootTweak::KeyOnly => f.write_str("none (key-path only)"),
+            TaprootTweak::MerkleRoot(root) => write!(f, "merkle root {}", hex::encode(root)),
+            TaprootTweak::Scalar(t) => write!(f, "scalar {}", hex::encode(t)),
+        }
+    }
+}
+
+/// Apply the BIP-341 tweak to an x-only internal key.
+///
+/// Returns the tweaked x-only output key and its parity.
+pub fn tweak_public_key(
+    internal_key: &[u8],
+    tweak: TaprootTweak,
+) -> Result<([u8; 32], Parity), String> {
+    let secp = Secp256k1::verification_only();
+    let t = Scalar::from_be_bytes(tweak.scalar(internal_key)?)
+        .map_err(|_| "TapTweak hash is not a valid scalar".to_string())?;
+    let internal_key = XOnlyPublicKey::from_slice(internal_key)
+        .map_err(|e| format!("Invalid internal key: {}", e))?;
+    let (output_key, parity) = internal_key
+        .add_tweak(&secp, &t)
+        .map_err(|e| format!("Failed to tweak internal key: {}", e))?;
+    Ok((output_key.serialize(), parity))
+}
+
+/// Derive the Taproot output for an x-only group public key.
+pub fn derive_taproot_output(
+    public_key: &[u8],
+    merkle_root: Option<[u8; 32]>,
+    network: Network,
+) -> Result<TaprootOutput, String> {
+    let internal_key: [u8; 32] = public_key
+        .try_into()
+        .map_err(|_| format!("Expected 32-byte x-only key, got {}", public_key.len()))?;
+    let (output_key, parity) =
+        tweak_public_key(&internal_key, TaprootTweak::from_merkle_root(merkle_root))?;
+
+    let tweaked = TweakedPublicKey::dangerous_assume_tweaked(
+        XOnlyPublicKey::from_slice(&output_key)
+            .map_err(|e| format!("Invalid output key: {}", e))?,
+    );
+    let script_pubkey = ScriptBuf::new_p2tr_tweaked(tweaked);
+    let address = Address::p2tr_tweaked(tweaked, network).to_string();
+
+    Ok(TaprootOutput {
+        internal_key,
+        merkle_root,
+        output_key,
+        parity,
+        script_pubkey,
+        address,
+    })
+}
+
+impl FrostKeygenResult {
+    /// Derive the Taproot output for this keygen's group public key.
+    pub fn taproot_output(
+        &self,
+        merkle_root: Option<[u8; 32]>,
+        network: Network,
+    ) -> Result<TaprootOutput, String> {
+        if self.ciphersuite != FrostCiphersuite::Bitcoin {
+            return Err(format!(
+                "Taproot outputs need a Bitcoin key, not {}",
+                self.ciphersuite
+            ));
+        }
+        let public_key = self
+            .public_key
+            .as_deref()
+            .ok_or_else(|| "Keygen result has no public key".to_string())?;
+        derive_taproot_output(public_key, merkle_root, network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_taproot_output_bip86_vector() {
+        // BIP-86 test vector: first receiving address of account 0
+        let internal_key =
+            hex::decode("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115")
+                .unwrap();
+        let output = derive_taproot_output(&internal_key, None, Network::Bitcoin).unwrap();
+
+        assert_eq!(
+            hex::encode(output.output_key),
+            "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+        );
+        assert_eq!(
+            hex::encode(output.script_pubkey.as_bytes()),
+            "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+        );
+        assert_eq!(
+            output.address,
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+    }
+
+    #[test]
+    fn test_derive_taproot_output_per_network() {
+        let internal_key =
+            hex::decode("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115")
+                .unwrap();
+        let mainnet = derive_taproot_output(&internal_key, None, Network::Bitcoin).unwrap();
+
+        for (network, address) in [
+            (
+                Network::Testnet,
+                "tb1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqp3mvzv",
+            ),
+            (
+                Network::Signet,
+                "tb1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqp3mvzv",
+            ),
+            (
+                Network::Regtest,
+                "bcrt1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqvg32hk",
+            ),
+        ] {
+            let output = derive_taproot_output(&internal_key, None, network).unwrap();
+            assert_eq!(output.address, address);
+            // Only the address encoding depends on the network
+            assert_eq!(output.output_key, mainnet.output_key);
+            assert_eq!(output.script_pubkey, mainnet.script_pubkey);
+        }
+    }
+
+    #[test]
+    fn test_raw_tweak_scalar_matches_merkle_root_tweak() {
+        let internal_key =
+            hex::decode("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115")
+                .unwrap();
+        let root = TaprootTweak::MerkleRoot([7u8; 32]);
+        let t = root.scalar(&internal_key).unwrap();
+
+        assert_eq!(
+            tweak_public_key(&internal_key, TaprootTweak::Scalar(t)).unwrap(),
+            tweak_public_key(&internal_key, root).unwrap()
+        );
+        assert_ne!(
+            tweak_public_key(&internal_key, TaprootTweak::KeyOnly).unwrap(),
+            tweak_public_key(&internal_key, root).unwrap()
+        );
+        assert!(tweak_public_key(&internal_key, TaprootTweak::Scalar([0xff; 32])).is_err());
+    }
+}
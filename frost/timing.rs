@@ -0,0 +1,180 @@
+//! Per-round timing of the protocol transport.
+//!
+//! The keygen and signing `ChannelStream`/`ChannelSink` and `RoundTransport`
+//! timestamp every message they receive and send. From these timestamps
+//! each round is split into local compute time, until this party sent its
+//! messages of the round, and network/wait time, until the last peer's
+//! message of the round arrived.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A message sent or received by the transport.
+#[derive(Debug, Clone, Copy)]
+struct TransportEvent {
+    round: u16,
+    /// Sender of a received message, `None` for sent messages
+    from: Option<u16>,
+    at: Instant,
+}
+
+/// Timestamps of all messages of one protocol run.
+///
+/// Rounds are numbered from 1, as they appear in benchmark reports. Givre's
+/// round-based rounds count from 0 and are recorded as `round + 1`;
+/// `RoundTransport` rounds already count from 1.
+#[derive(Debug)]
+pub struct TransportTimings {
+    start: Instant,
+    events: Vec<TransportEvent>,
+}
+
+/// Timings shared between the stream and sink of one protocol run.
+pub(crate) type TransportLog = Arc<Mutex<TransportTimings>>;
+
+/// Compute and wait time of one round, as seen by this party.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTiming {
+    /// Round number, from 1
+    pub round: u16,
+    /// From the end of the previous round until this party's last message
+    /// of the round was sent
+    pub compute: Duration,
+    /// From then until the last peer message of the round arrived
+    pub wait: Duration,
+    /// Wait for each peer's message of the round
+    pub peer_wait: BTreeMap<u16, Duration>,
+}
+
+impl TransportTimings {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            events: Vec::new(),
+        }
+    }
+
+    pub(crate) fn sent(&mut self, round: u16) {
+        self.events.push(TransportEvent {
+            round,
+            from: None,
+            at: Instant::now(),
+        });
+    }
+
+    pub(crate) fn received(&mut self, round: u16, sender: u16) {
+        self.events.push(TransportEvent {
+            round,
+            from: Some(sender),
+            at: Instant::now(),
+        });
+    }
+
+    /// Split the run into rounds. Whatever follows the last round until `end`
+    /// is returned separately as output computation.
+    pub fn rounds(&self, end: Instant) -> (Vec<RoundTiming>, Duration) {
+        let mut by_round: BTreeMap<u16, Vec<&TransportEvent>> = BTreeMap::new();
+        for event in &self.events {
+            by_round.entry(event.round).or_default().push(event);
+        }
+
+        let mut round_start = self.start;
+        let mut rounds = Vec::with_capacity(by_round.len());
+        for (round, events) in by_round {
+            let sent_until = events
+                .iter()
+                .filter(|e| e.from.is_none())
+                .map(|e| e.at)
+                .max()
+                .unwrap_or(round_start)
+                .max(round_start);
+
+            let mut peer_wait = BTreeMap::new();
+            let mut round_end = sent_until;
+            for event in &events {
+                if let Some(sender) = event.from {
+                    let wait = event.at.saturating_duration_since(sent_until);
+                    let slowest = peer_wait.entry(sender).or_insert(Duration::ZERO);
+                    *slowest = (*slowest).max(wait);
+                    round_end = round_end.max(event.at);
+                }
+            }
+
+            rounds.push(RoundTiming {
+                round,
+                compute: sent_until.saturating_duration_since(round_start),
+                wait: round_end.saturating_duration_since(sent_until),
+                peer_wait,
+            });
+            round_start = round_end;
+        }
+        (rounds, end.saturating_duration_since(round_start))
+    }
+
+    /// Record the per-round split as sub-steps of benchmark step `step`.
+    pub(crate) fn record(&self, step: u32, end: Instant, mut record: impl FnMut(&str, Duration)) {
+        let (rounds, output) = self.rounds(end);
+        for timing in &rounds {
+            let n = u32::from(timing.round);
+            record(
+                &format!("{}.{} Round {}: local compute", step, n, n),
+                timing.compute,
+            );
+            record(
+                &format!("{}.{} Round {}: network wait", step, n, n),
+                timing.wait,
+            );
+            for (peer, wait) in &timing.peer_wait {
+                record(
+                    &format!("{}.{} Round {}: waiting for party {}", step, n, n, peer),
+                    *wait,
+                );
+            }
+        }
+        let n = rounds.last().map_or(0, |t| u32::from(t.round)) + 1;
+        record(&format!("{}.{} Output computation", step, n), output);
+    }
+}
+
+impl Default for TransportTimings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounds_split_compute_from_wait() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let event = |round, from, at| TransportEvent { round, from, at };
+        let timings = TransportTimings {
+            start,
+            events: vec![
+                event(1, None, ms(10)),
+                event(1, Some(1), ms(15)),
+                event(1, Some(2), ms(40)),
+                // Party 1 was ahead and sent its round 2 message early
+                event(2, Some(1), ms(45)),
+                event(2, None, ms(60)),
+                event(2, Some(2), ms(70)),
+            ],
+        };
+
+        let (rounds, output) = timings.rounds(ms(75));
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[0].round, 1);
+        assert_eq!(rounds[0].compute, Duration::from_millis(10));
+        assert_eq!(rounds[0].wait, Duration::from_millis(30));
+        assert_eq!(rounds[0].peer_wait[&1], Duration::from_millis(5));
+        assert_eq!(rounds[0].peer_wait[&2], Duration::from_millis(30));
+        assert_eq!(rounds[1].compute, Duration::from_millis(20));
+        assert_eq!(rounds[1].wait, Duration::from_millis(10));
+        assert_eq!(rounds[1].peer_wait[&1], Duration::ZERO);
+        assert_eq!(output, Duration::from_millis(5));
+    }
+}
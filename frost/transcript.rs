@@ -0,0 +1,672 @@
+//! Signed keygen transcripts for offline audit.
+//!
+//! Every party records the broadcast messages of its keygen session (the
+//! commitments, decommitments and proofs of knowledge; private shares are
+//! never broadcast and never recorded) together with the session
+//! parameters, timings and resulting public key and public shares. The
+//! party then signs the transcript, so the transcript also proves which
+//! party produced it.
+//!
+//! The signing key is not the key share itself but a key derived from it
+//! under its own tag, `x + H(tag || X)` for share `x` with public share `X`.
+//! Its public key follows from the public share, so an auditor can still tie
+//! the signature to the party without a PKI.
+//!
+//! An auditor can check a transcript offline with
+//! [`verify_keygen_transcript`], replay the checks of the recorded
+//! commitments and proofs with [`verify_keygen_broadcasts`], and check that
+//! all parties saw the same session with [`compare_keygen_transcripts`].
+//! Signing sessions are recorded as a [`SigningTranscript`].
+
+use std::sync::{Arc, Mutex};
+
+use generic_ec::{Curve, Point, Scalar};
+use generic_ec_zkp::schnorr_pok;
+use givre::keygen::msg::threshold::Msg;
+use givre::keygen::security_level::{SecurityLevel, SecurityLevel128};
+use givre::keygen::ExecutionId;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use givre::ciphersuite::{Bitcoin, Ciphersuite, Ed25519, Ristretto255, Secp256k1};
+
+use super::ciphersuite::FrostCiphersuite;
+use super::keygen::{
+    FrostKeygenMsg, KeygenDigest, KeygenOptions, KeygenSecurityLevel, SecurityLevel256,
+};
+use super::rounds::{group_public_key, FrostKeyInfo};
+use super::signing::{SchnorrSignature, SigningMode, SigningOptions};
+use super::taproot::{tweak_public_key, TaprootTweak};
+
+/// Domain separator for transcript signatures.
+const TRANSCRIPT_SIGNATURE_TAG: &[u8] = b"FROST/keygen-transcript";
+
+/// Domain separator for deriving the transcript key from a key share.
+const TRANSCRIPT_KEY_TAG: &[u8] = b"FROST/keygen-transcript-key";
+
+/// One broadcast message seen during keygen.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub sender: u16,
+    /// Serialized Givre keygen message
+    pub payload: Vec<u8>,
+}
+
+/// Broadcast messages collected by the keygen transport.
+pub(crate) type TranscriptLog = Arc<Mutex<Vec<TranscriptEntry>>>;
+
+/// Everything one party saw and produced during keygen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeygenTranscript {
+    pub session_id: String,
+    pub options: KeygenOptions,
+    /// Index at keygen of the party that recorded the transcript
+    pub party_index: u16,
+    pub num_parties: u16,
+    pub threshold: u16,
+    /// Broadcast messages of all parties, sorted by sender
+    pub broadcasts: Vec<TranscriptEntry>,
+    /// Start of the keygen (seconds since the Unix epoch)
+    pub started_at: u64,
+    pub duration_secs: f64,
+    /// Group public key (compressed)
+    pub shared_public_key: Vec<u8>,
+    /// Public share of every party (compressed), by index at keygen
+    pub public_shares: Vec<Vec<u8>>,
+    /// Evaluation point of every party's share (big-endian scalar)
+    pub share_preimages: Vec<Vec<u8>>,
+}
+
+/// A keygen transcript signed with the recording party's key share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedKeygenTranscript {
+    pub transcript: KeygenTranscript,
+    /// Schnorr signature (R || s) under the party's transcript key
+    pub signature: Vec<u8>,
+}
+
+impl KeygenTranscript {
+    /// SHA-256 of the serialized transcript, as signed.
+    pub fn digest(&self) -> Result<[u8; 32], String> {
+        let bytes = serde_json::to_vec(self)
+            .map_err(|e| format!("Failed to serialize transcript: {}", e))?;
+        Ok(Sha256::digest(bytes).into())
+    }
+
+    /// Sign the transcript with the transcript key derived from
+    /// `secret_share`, the key share of `party_index`.
+    pub(crate) fn sign<E: Curve>(
+        self,
+        secret_share: &Scalar<E>,
+    ) -> Result<SignedKeygenTranscript, String> {
+        let digest = self.digest()?;
+        let public_share = Point::generator() * secret_share;
+        let transcript_secret = secret_share + transcript_key_tweak(&public_share);
+        let transcript_key = Point::generator() * transcript_secret;
+        let k = Scalar::<E>::random(&mut OsRng);
+        let r = Point::generator() * k;
+        let e = challenge(&r, &transcript_key, &digest);
+        let s = k + e * transcript_secret;
+
+        let mut signature = r.to_bytes(true).to_vec();
+        signature.extend_from_slice(&s.to_be_bytes());
+        Ok(SignedKeygenTranscript {
+            transcript: self,
+            signature,
+        })
+    }
+}
+
+/// Tweak from a public share to the transcript key of its party.
+fn transcript_key_tweak<E: Curve>(public_share: &Point<E>) -> Scalar<E> {
+    Scalar::from_be_bytes_mod_order(
+        Sha256::new()
+            .chain_update(TRANSCRIPT_KEY_TAG)
+            .chain_update(public_share.to_bytes(true))
+            .finalize(),
+    )
+}
+
+/// Public transcript key of the party with `public_share`.
+fn transcript_key<E: Curve>(public_share: &Point<E>) -> Point<E> {
+    public_share + Point::generator() * transcript_key_tweak(public_share)
+}
+
+fn challenge<E: Curve>(r: &Point<E>, transcript_key: &Point<E>, digest: &[u8; 32]) -> Scalar<E> {
+    Scalar::from_be_bytes_mod_order(
+        Sha256::new()
+            .chain_update(TRANSCRIPT_SIGNATURE_TAG)
+            .chain_update(r.to_bytes(true))
+            .chain_update(transcript_key.to_bytes(true))
+            .chain_update(digest)
+            .finalize(),
+    )
+}
+
+/// Check a signed transcript offline.
+///
+/// Verifies that it was signed by the party it names, and that all public
+/// shares lie on one polynomial of degree `threshold - 1` whose value at
+/// zero is the group public key.
+pub fn verify_keygen_transcript(signed: &SignedKeygenTranscript) -> Result<(), String> {
+    match signed.transcript.options.ciphersuite {
+        FrostCiphersuite::Bitcoin => verify::<<Bitcoin as Ciphersuite>::Curve>(signed),
+        FrostCiphersuite::Secp256k1 => verify::<<Secp256k1 as Ciphersuite>::Curve>(signed),
+        FrostCiphersuite::Ed25519 => verify::<<Ed25519 as Ciphersuite>::Curve>(signed),
+        FrostCiphersuite::Ristretto255 => verify::<<Ristretto255 as Ciphersuite>::Curve>(signed),
+    }
+}
+
+fn verify<E: Curve>(signed: &SignedKeygenTranscript) -> Result<(), String> {
+    let t = &signed.transcript;
+    let parse_point = |bytes: &[u8]| {
+        Point::<E>::from_bytes(bytes).map_err(|_| "Invalid point in transcript".to_string())
+    };
+    let parse_scalar = |bytes: &[u8]| {
+        Scalar::<E>::from_be_bytes(bytes).map_err(|_| "Invalid scalar in transcript".to_string())
+    };
+
+    let n = usize::from(t.num_parties);
+    let threshold = usize::from(t.threshold);
+    if t.public_shares.len() != n || t.share_preimages.len() != n {
+        return Err(format!(
+            "Transcript lists {} public shares and {} preimages for {} parties",
+            t.public_shares.len(),
+            t.share_preimages.len(),
+            n
+        ));
+    }
+    if threshold == 0 || threshold > n {
+        return Err(format!("Invalid threshold {}-of-{}", t.threshold, n));
+    }
+    let shares = t
+        .public_shares
+        .iter()
+        .map(|s| parse_point(s))
+        .collect::<Result<Vec<_>, _>>()?;
+    let preimages = t
+        .share_preimages
+        .iter()
+        .map(|s| parse_scalar(s))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Signature under the recording party's transcript key
+    let public_share = shares
+        .get(usize::from(t.party_index))
+        .ok_or_else(|| format!("Party index {} out of range", t.party_index))?;
+    let transcript_key = transcript_key(public_share);
+    let point_len = public_share.to_bytes(true).len();
+    if signed.signature.len() <= point_len {
+        return Err("Transcript signature is truncated".to_string());
+    }
+    let r = parse_point(&signed.signature[..point_len])?;
+    let s = parse_scalar(&signed.signature[point_len..])?;
+    let e = challenge(&r, &transcript_key, &t.digest()?);
+    if Point::generator() * s != r + transcript_key * e {
+        return Err(format!(
+            "Transcript signature of party {} is invalid",
+            t.party_index
+        ));
+    }
+
+    // Public shares must be consistent with the group key
+    let (base_x, base_y) = (&preimages[..threshold], &shares[..threshold]);
+    for (j, (x_j, y_j)) in preimages.iter().zip(&shares).enumerate().skip(threshold) {
+        if interpolate(base_x, base_y, x_j)? != *y_j {
+            return Err(format!("Public share of party {} is inconsistent", j));
+        }
+    }
+    if interpolate(base_x, base_y, &Scalar::zero())? != parse_point(&t.shared_public_key)? {
+        return Err("Public shares do not interpolate to the group public key".to_string());
+    }
+    Ok(())
+}
+
+/// Evaluate the polynomial through points `(xs[i], ys[i] = f(xs[i]) G)` at `at`.
+fn interpolate<E: Curve>(
+    xs: &[Scalar<E>],
+    ys: &[Point<E>],
+    at: &Scalar<E>,
+) -> Result<Point<E>, String> {
+    let mut result = Point::zero();
+    for (j, (x_j, y_j)) in xs.iter().zip(ys).enumerate() {
+        let mut numerator = Scalar::<E>::one();
+        let mut denominator = Scalar::<E>::one();
+        for (m, x_m) in xs.iter().enumerate() {
+            if m != j {
+                numerator *= at - x_m;
+                denominator *= x_j - x_m;
+            }
+        }
+        let denominator_inv = denominator
+            .invert()
+            .ok_or_else(|| "Duplicate share preimages in transcript".to_string())?;
+        result += y_j * (numerator * denominator_inv);
+    }
+    Ok(result)
+}
+
+/// Check that transcripts from different parties describe the same keygen.
+///
+/// Every transcript must verify, and all must agree on the session, the
+/// parameters, the resulting keys and every broadcast message. A mismatch
+/// in the broadcasts means some party equivocated.
+pub fn compare_keygen_transcripts(transcripts: &[SignedKeygenTranscript]) -> Result<(), String> {
+    let first = transcripts
+        .first()
+        .map(|s| &s.transcript)
+        .ok_or_else(|| "No transcripts to compare".to_string())?;
+    for signed in transcripts {
+        verify_keygen_transcript(signed)?;
+        let t = &signed.transcript;
+        if t.session_id != first.session_id
+            || t.options != first.options
+            || t.num_parties != first.num_parties
+            || t.threshold != first.threshold
+        {
+            return Err(format!(
+                "Party {} recorded different session parameters",
+                t.party_index
+            ));
+        }
+        if t.shared_public_key != first.shared_public_key || t.public_shares != first.public_shares
+        {
+            return Err(format!("Party {} recorded a different key", t.party_index));
+        }
+        if t.broadcasts != first.broadcasts {
+            return Err(format!(
+                "Party {} saw different broadcasts than party {}",
+                t.party_index, first.party_index
+            ));
+        }
+    }
+    Ok(())
+}
+
+// The two udigest tags below are copied from cggmp21-keygen 0.4
+// (src/threshold.rs), the keygen that givre 0.2 re-exports as
+// `givre::keygen`. They are not exported, so a givre upgrade that changes
+// them breaks the replay silently; `test_replay_matches_givre_keygen` runs a
+// real keygen for every digest and security level to catch that.
+
+/// Opening of a party's round 1 commitment, hashed as Givre's keygen does.
+#[derive(udigest::Digestable)]
+#[udigest(tag = "dfns.cggmp21.keygen.threshold.hash_com")]
+#[udigest(bound = "")]
+struct HashCom<'a, T: udigest::Digestable> {
+    sid: ExecutionId<'a>,
+    party_index: u16,
+    decommitment: &'a T,
+}
+
+/// Challenge of a party's proof of knowledge, derived as Givre's keygen does.
+#[derive(udigest::Digestable)]
+#[udigest(tag = "dfns.cggmp21.keygen.threshold.schnorr_pok")]
+struct SchnorrPok<'a> {
+    sid: ExecutionId<'a>,
+    prover: u16,
+    #[udigest(as_bytes)]
+    rid: &'a [u8],
+}
+
+/// Replay the public checks of keygen on the recorded broadcasts.
+///
+/// Checks that every party's round 1 commitment opens to its round 2
+/// decommitment, that its proof of knowledge of its secret constant term
+/// verifies, and that the VSS commitments of all parties sum to the group
+/// public key and evaluate to every party's public share. With reliable
+/// broadcast, also checks that all parties echoed the same round 1 messages.
+pub fn verify_keygen_broadcasts(signed: &SignedKeygenTranscript) -> Result<(), String> {
+    let t = &signed.transcript;
+    match t.options.ciphersuite {
+        FrostCiphersuite::Bitcoin => replay_with_level::<<Bitcoin as Ciphersuite>::Curve>(t),
+        FrostCiphersuite::Secp256k1 => replay_with_level::<<Secp256k1 as Ciphersuite>::Curve>(t),
+        FrostCiphersuite::Ed25519 => replay_with_level::<<Ed25519 as Ciphersuite>::Curve>(t),
+        FrostCiphersuite::Ristretto255 => {
+            replay_with_level::<<Ristretto255 as Ciphersuite>::Curve>(t)
+        }
+    }
+}
+
+fn replay_with_level<E: Curve>(t: &KeygenTranscript) -> Result<(), String> {
+    match t.options.security_level {
+        KeygenSecurityLevel::Bits128 => replay_with_digest::<E, SecurityLevel128>(t),
+        KeygenSecurityLevel::Bits256 => replay_with_digest::<E, SecurityLevel256>(t),
+    }
+}
+
+fn replay_with_digest<E: Curve, L: SecurityLevel>(t: &KeygenTranscript) -> Result<(), String> {
+    match t.options.digest {
+        KeygenDigest::Sha256 => replay::<E, L, sha2::Sha256>(t),
+        KeygenDigest::Sha512 => replay::<E, L, sha2::Sha512>(t),
+    }
+}
+
+fn replay<E: Curve, L: SecurityLevel, D: Digest + Clone + 'static>(
+    t: &KeygenTranscript,
+) -> Result<(), String> {
+    let n = usize::from(t.num_parties);
+    let threshold = usize::from(t.threshold);
+    let mut commitments: Vec<Option<_>> = (0..n).map(|_| None).collect();
+    let mut decommitments: Vec<Option<_>> = (0..n).map(|_| None).collect();
+    let mut proofs: Vec<Option<_>> = (0..n).map(|_| None).collect();
+    let mut echoes = Vec::new();
+    for entry in &t.broadcasts {
+        let j = usize::from(entry.sender);
+        if j >= n {
+            return Err(format!("Broadcast from unknown party {}", entry.sender));
+        }
+        let msg: FrostKeygenMsg<E, L, D> = serde_json::from_slice(&entry.payload)
+            .map_err(|e| format!("Broadcast of party {} is malformed: {}", j, e))?;
+        let repeated = match msg {
+            Msg::Round1(m) => commitments[j].replace(m.commitment).is_some(),
+            Msg::Round2Broad(m) => decommitments[j].replace(m).is_some(),
+            Msg::Round3(m) => proofs[j].replace(m.sch_proof).is_some(),
+            Msg::ReliabilityCheck(m) => {
+                echoes.push(m.0);
+                false
+            }
+            Msg::Round2Uni(_) => return Err(format!("Party {} broadcast a private share", j)),
+        };
+        if repeated {
+            return Err(format!("Party {} broadcast twice in one round", j));
+        }
+    }
+
+    let missing = |what: &str, j: usize| format!("No {} from party {} in transcript", what, j);
+    let eid = t.options.execution_id(&t.session_id);
+    let sid = ExecutionId::new(&eid);
+
+    // Commitments must open, and the joint randomness is the XOR of all openings
+    let mut rid = L::Rid::default();
+    let mut vss = Vec::with_capacity(n);
+    for j in 0..n {
+        let commitment = commitments[j]
+            .as_ref()
+            .ok_or_else(|| missing("commitment", j))?;
+        let decommitment = decommitments[j]
+            .as_ref()
+            .ok_or_else(|| missing("decommitment", j))?;
+        let opened = udigest::hash::<D>(&HashCom {
+            sid,
+            party_index: j as u16,
+            decommitment,
+        });
+        if *commitment != opened {
+            return Err(format!(
+                "Decommitment of party {} does not open its commitment",
+                j
+            ));
+        }
+        if decommitment.F.coefs().len() != threshold {
+            return Err(format!(
+                "VSS commitment of party {} has {} coefficients for threshold {}",
+                j,
+                decommitment.F.coefs().len(),
+                threshold
+            ));
+        }
+        rid.as_mut()
+            .iter_mut()
+            .zip(decommitment.rid.as_ref())
+            .for_each(|(r, x)| *r ^= x);
+        vss.push(&decommitment.F);
+    }
+
+    // Proofs of knowledge of every party's constant term
+    for (j, (decommitment, proof)) in decommitments.iter().zip(&proofs).enumerate() {
+        let decommitment = decommitment
+            .as_ref()
+            .ok_or_else(|| missing("decommitment", j))?;
+        let proof = proof
+            .as_ref()
+            .ok_or_else(|| missing("proof of knowledge", j))?;
+        let challenge = schnorr_pok::Challenge {
+            nonce: Scalar::<E>::from_hash::<D>(&SchnorrPok {
+                sid,
+                prover: j as u16,
+                rid: rid.as_ref(),
+            }),
+        };
+        proof
+            .verify(
+                &decommitment.sch_commit,
+                &challenge,
+                &decommitment.F.coefs()[0],
+            )
+            .map_err(|_| format!("Proof of knowledge of party {} is invalid", j))?;
+    }
+
+    // VSS: the summed polynomial gives the group key and every public share
+    let parse_point = |bytes: &[u8]| {
+        Point::<E>::from_bytes(bytes).map_err(|_| "Invalid point in transcript".to_string())
+    };
+    let group_key = vss
+        .iter()
+        .fold(Point::<E>::zero(), |acc, f| acc + f.coefs()[0]);
+    if group_key != parse_point(&t.shared_public_key)? {
+        return Err("VSS commitments do not sum to the group public key".to_string());
+    }
+    if t.public_shares.len() != n || t.share_preimages.len() != n {
+        return Err("Transcript does not list every party's public share".to_string());
+    }
+    for (i, (share, preimage)) in t.public_shares.iter().zip(&t.share_preimages).enumerate() {
+        let x = Scalar::<E>::from_be_bytes(preimage)
+            .map_err(|_| "Invalid scalar in transcript".to_string())?;
+        let expected = vss.iter().fold(Point::<E>::zero(), |acc, f| {
+            acc + f.value::<_, Point<E>>(&x)
+        });
+        if expected != parse_point(share)? {
+            return Err(format!(
+                "Public share of party {} does not match the VSS commitments",
+                i
+            ));
+        }
+    }
+
+    // Reliable broadcast: every party echoed the same round 1 messages
+    if t.options.reliable_broadcast
+        && (echoes.len() != n || echoes.windows(2).any(|w| w[0] != w[1]))
+    {
+        return Err("Parties echoed different round 1 broadcasts".to_string());
+    }
+    Ok(())
+}
+
+/// Public record of one FROST signing session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningTranscript {
+    pub session_id: String,
+    /// Signers (index at keygen)
+    pub signers: Vec<u16>,
+    pub message_hash: [u8; 32],
+    pub mode: SigningMode,
+    pub taproot_tweak: TaprootTweak,
+    /// Untweaked x-only group key
+    pub internal_key: [u8; 32],
+    /// Key the signature verifies against
+    pub output_key: [u8; 32],
+    pub signature: SchnorrSignature,
+}
+
+impl SigningTranscript {
+    /// Record a successful signing session with the key of `key_info`.
+    pub fn new(
+        session_id: &str,
+        signers: &[u16],
+        message_hash: &[u8; 32],
+        key_info: &FrostKeyInfo,
+        options: &SigningOptions,
+        signature: SchnorrSignature,
+        output_key: [u8; 32],
+    ) -> Self {
+        let mut internal_key = [0u8; 32];
+        internal_key.copy_from_slice(&group_public_key(key_info)[1..]);
+        Self {
+            session_id: session_id.to_string(),
+            signers: signers.to_vec(),
+            message_hash: *message_hash,
+            mode: options.mode,
+            taproot_tweak: options.taproot_tweak,
+            internal_key,
+            output_key,
+            signature,
+        }
+    }
+}
+
+/// Check that the output key matches the internal key and signing mode.
+pub fn verify_signing_output_key(transcript: &SigningTranscript) -> Result<(), String> {
+    let expected = if transcript.mode.is_tweaked() {
+        tweak_public_key(&transcript.internal_key, transcript.taproot_tweak)?.0
+    } else if transcript.taproot_tweak != TaprootTweak::KeyOnly {
+        return Err(format!(
+            "Taproot tweak recorded in {:?} mode",
+            transcript.mode
+        ));
+    } else {
+        transcript.internal_key
+    };
+    if expected != transcript.output_key {
+        return Err(format!(
+            "Output key {} does not match internal key {} in {:?} mode",
+            hex::encode(transcript.output_key),
+            hex::encode(transcript.internal_key),
+            transcript.mode
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::local;
+    use super::*;
+
+    type E = <Bitcoin as Ciphersuite>::Curve;
+
+    #[test]
+    fn test_signed_transcript_verifies() {
+        // 2-of-3 sharing of secret 7 with f(x) = 7 + 5x
+        let f = |x: u64| Scalar::<E>::from(7u64) + Scalar::<E>::from(5u64) * Scalar::from(x);
+        let secret_share = f(2);
+        let transcript = KeygenTranscript {
+            session_id: "audit".to_string(),
+            options: KeygenOptions::default(),
+            party_index: 1,
+            num_parties: 3,
+            threshold: 2,
+            broadcasts: vec![TranscriptEntry {
+                sender: 0,
+                payload: b"{}".to_vec(),
+            }],
+            started_at: 0,
+            duration_secs: 0.5,
+            shared_public_key: (Point::<E>::generator() * f(0)).to_bytes(true).to_vec(),
+            public_shares: (1..=3)
+                .map(|x| (Point::<E>::generator() * f(x)).to_bytes(true).to_vec())
+                .collect(),
+            share_preimages: (1..=3u64)
+                .map(|x| Scalar::<E>::from(x).to_be_bytes().to_vec())
+                .collect(),
+        };
+
+        let signed = transcript.sign(&secret_share).unwrap();
+        assert!(verify_keygen_transcript(&signed).is_ok());
+        assert!(compare_keygen_transcripts(&[signed.clone(), signed.clone()]).is_ok());
+
+        // A signature made with the key share itself is not accepted
+        let public_share = Point::<E>::generator() * secret_share;
+        let k = Scalar::<E>::random(&mut OsRng);
+        let r = Point::<E>::generator() * k;
+        let e = challenge(&r, &public_share, &signed.transcript.digest().unwrap());
+        let mut share_signed = signed.clone();
+        share_signed.signature = r.to_bytes(true).to_vec();
+        share_signed
+            .signature
+            .extend_from_slice(&(k + e * secret_share).to_be_bytes());
+        assert!(verify_keygen_transcript(&share_signed).is_err());
+
+        let mut tampered = signed.clone();
+        tampered.transcript.threshold = 3;
+        assert!(verify_keygen_transcript(&tampered).is_err());
+
+        let mut wrong_key = signed;
+        wrong_key.transcript.public_shares[2] =
+            (Point::<E>::generator() * f(9)).to_bytes(true).to_vec();
+        assert!(verify_keygen_transcript(&wrong_key).is_err());
+    }
+
+    #[test]
+    fn test_keygen_transcripts_of_all_parties_agree() {
+        for ciphersuite in [FrostCiphersuite::Bitcoin, FrostCiphersuite::Ed25519] {
+            let options = KeygenOptions {
+                ciphersuite,
+                reliable_broadcast: ciphersuite == FrostCiphersuite::Ed25519,
+                ..Default::default()
+            };
+            let transcripts: Vec<SignedKeygenTranscript> = local::run_keygen(2, 3, &options)
+                .into_iter()
+                .map(|result| {
+                    assert!(result.success, "keygen failed: {:?}", result.error);
+                    result.transcript.unwrap()
+                })
+                .collect();
+            for signed in &transcripts {
+                assert!(verify_keygen_transcript(signed).is_ok());
+                assert!(verify_keygen_broadcasts(signed).is_ok());
+            }
+            assert!(compare_keygen_transcripts(&transcripts).is_ok());
+
+            // A proof of knowledge copied from another party does not verify
+            let mut replayed = transcripts[0].clone();
+            let broadcasts = &mut replayed.transcript.broadcasts;
+            let is_proof = |e: &TranscriptEntry| e.payload.starts_with(b"{\"Round3\"");
+            let proof_of_1 = broadcasts
+                .iter()
+                .find(|e| e.sender == 1 && is_proof(e))
+                .unwrap()
+                .payload
+                .clone();
+            let proof_of_0 = broadcasts
+                .iter_mut()
+                .find(|e| e.sender == 0 && is_proof(e))
+                .unwrap();
+            proof_of_0.payload = proof_of_1;
+            assert_eq!(
+                verify_keygen_broadcasts(&replayed),
+                Err("Proof of knowledge of party 0 is invalid".to_string())
+            );
+
+            // A party cannot alter what it saw after signing
+            let mut tampered = transcripts.clone();
+            tampered[1].transcript.broadcasts.pop();
+            assert!(compare_keygen_transcripts(&tampered).is_err());
+        }
+    }
+
+    #[test]
+    fn test_replay_matches_givre_keygen() {
+        for security_level in [KeygenSecurityLevel::Bits128, KeygenSecurityLevel::Bits256] {
+            for digest in [KeygenDigest::Sha256, KeygenDigest::Sha512] {
+                let options = KeygenOptions {
+                    security_level,
+                    digest,
+                    ..Default::default()
+                };
+                for result in local::run_keygen(2, 2, &options) {
+                    assert!(result.success, "keygen failed: {:?}", result.error);
+                    assert_eq!(
+                        verify_keygen_broadcasts(&result.transcript.unwrap()),
+                        Ok(()),
+                        "replay of a real {:?}/{:?} keygen failed; check the udigest \
+                         tags of HashCom and SchnorrPok against the givre dependency",
+                        security_level,
+                        digest
+                    );
+                }
+            }
+        }
+    }
+}